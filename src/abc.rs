@@ -0,0 +1,180 @@
+use crate::clock::DateRange;
+use crate::inventory::{Inventory, InventoryError, Product};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Pareto class of a product: A items bring in most of the contribution, C items
+/// the long tail.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AbcClass {
+    A,
+    B,
+    C,
+}
+
+impl AbcClass {
+    pub const ALL: [AbcClass; 3] = [AbcClass::A, AbcClass::B, AbcClass::C];
+}
+
+impl fmt::Display for AbcClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbcClass::A => write!(f, "A"),
+            AbcClass::B => write!(f, "B"),
+            AbcClass::C => write!(f, "C"),
+        }
+    }
+}
+
+/// What a product's contribution is measured by.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AbcBasis {
+    Revenue,
+    Profit,
+}
+
+/// Cumulative share, in percent, of total contribution covered by class A and by
+/// classes A and B together. Everything beyond `b_percent` is class C.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AbcThresholds {
+    pub a_percent: f64,
+    pub b_percent: f64,
+}
+
+impl Default for AbcThresholds {
+    fn default() -> Self {
+        AbcThresholds {
+            a_percent: 80.0,
+            b_percent: 95.0,
+        }
+    }
+}
+
+impl Product {
+    /// Whether the product belongs to `class`; `None` matches every product.
+    pub fn is_in_class(&self, class: Option<AbcClass>) -> bool {
+        class.is_none() || self.abc_class == class
+    }
+}
+
+#[allow(dead_code)]
+pub trait AbcManagement {
+    fn classify_products(
+        &mut self,
+        range: &DateRange,
+        basis: AbcBasis,
+        thresholds: AbcThresholds,
+    ) -> Result<(), InventoryError>;
+    fn products_in_class(&self, class: AbcClass) -> Vec<String>;
+}
+
+impl AbcManagement for Inventory {
+    // Products are ranked by net contribution over the range, and each one is
+    // classed by the cumulative share of the products ranked above it, so the top
+    // seller is always class A. Products that contributed nothing are class C.
+    fn classify_products(
+        &mut self,
+        range: &DateRange,
+        basis: AbcBasis,
+        thresholds: AbcThresholds,
+    ) -> Result<(), InventoryError> {
+        if !(0.0 < thresholds.a_percent && thresholds.a_percent < thresholds.b_percent && thresholds.b_percent <= 100.0) {
+            return Err(InventoryError::InvalidInput(format!(
+                "class thresholds must satisfy 0 < A ({}) < B ({}) <= 100",
+                thresholds.a_percent, thresholds.b_percent
+            )));
+        }
+
+        let lines = self.sales_by_product(range);
+        let contribution = |name: &str| {
+            lines.get(name).map_or(0.0, |line| match basis {
+                AbcBasis::Revenue => line.revenue,
+                AbcBasis::Profit => line.profit,
+            })
+        };
+        let mut ranked: Vec<(usize, f64)> = self
+            .products
+            .iter()
+            .enumerate()
+            .map(|(index, product)| (index, contribution(&product.name).max(0.0)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let total: f64 = ranked.iter().map(|(_, value)| value).sum();
+
+        let mut cumulative = 0.0;
+        for (index, value) in ranked {
+            let share_before = if total > 0.0 { cumulative / total * 100.0 } else { 100.0 };
+            let class = if value <= 0.0 || share_before >= thresholds.b_percent {
+                AbcClass::C
+            } else if share_before < thresholds.a_percent {
+                AbcClass::A
+            } else {
+                AbcClass::B
+            };
+            self.products[index].abc_class = Some(class);
+            cumulative += value;
+        }
+        Ok(())
+    }
+
+    fn products_in_class(&self, class: AbcClass) -> Vec<String> {
+        self.products
+            .iter()
+            .filter(|p| p.abc_class == Some(class))
+            .map(|p| p.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+
+    fn inventory_with_sales() -> Inventory {
+        let mut inventory = Inventory::new();
+        for name in ["Laptop", "Monitor", "Mouse", "Cable"] {
+            inventory.add_product(name.to_string(), String::new(), 10.0, 100).unwrap();
+        }
+        inventory.record_sale("Laptop", 1, 850.0).unwrap();
+        inventory.record_sale("Monitor", 1, 100.0).unwrap();
+        inventory.record_sale("Mouse", 1, 50.0).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_classify_by_revenue() {
+        let mut inventory = inventory_with_sales();
+
+        inventory
+            .classify_products(&DateRange::all(), AbcBasis::Revenue, AbcThresholds::default())
+            .unwrap();
+
+        let classes: Vec<Option<AbcClass>> = inventory.products.iter().map(|p| p.abc_class).collect();
+        assert_eq!(
+            classes,
+            vec![Some(AbcClass::A), Some(AbcClass::B), Some(AbcClass::C), Some(AbcClass::C)]
+        );
+        assert_eq!(inventory.products_in_class(AbcClass::A), vec!["Laptop".to_string()]);
+    }
+
+    #[test]
+    fn test_classify_with_custom_thresholds() {
+        let mut inventory = inventory_with_sales();
+
+        let thresholds = AbcThresholds {
+            a_percent: 50.0,
+            b_percent: 99.0,
+        };
+        inventory.classify_products(&DateRange::all(), AbcBasis::Revenue, thresholds).unwrap();
+        assert_eq!(inventory.products[2].abc_class, Some(AbcClass::B));
+
+        let thresholds = AbcThresholds {
+            a_percent: 90.0,
+            b_percent: 80.0,
+        };
+        let result = inventory.classify_products(&DateRange::all(), AbcBasis::Profit, thresholds);
+        assert!(matches!(result, Err(InventoryError::InvalidInput(_))));
+    }
+}
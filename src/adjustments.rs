@@ -0,0 +1,208 @@
+use crate::inventory::{Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AdjustmentReason {
+    Damage,
+    Theft,
+    Found,
+    CountCorrection,
+    Sample,
+}
+
+impl AdjustmentReason {
+    pub const ALL: [AdjustmentReason; 5] = [
+        AdjustmentReason::Damage,
+        AdjustmentReason::Theft,
+        AdjustmentReason::Found,
+        AdjustmentReason::CountCorrection,
+        AdjustmentReason::Sample,
+    ];
+
+    /// Whether a stock change in the given direction makes sense for this reason:
+    /// damage, theft and samples only ever remove stock, found goods only add it.
+    fn allows(&self, quantity_change: i64) -> bool {
+        match self {
+            AdjustmentReason::Damage | AdjustmentReason::Theft | AdjustmentReason::Sample => quantity_change < 0,
+            AdjustmentReason::Found => quantity_change > 0,
+            AdjustmentReason::CountCorrection => quantity_change != 0,
+        }
+    }
+}
+
+impl fmt::Display for AdjustmentReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdjustmentReason::Damage => write!(f, "Damage"),
+            AdjustmentReason::Theft => write!(f, "Theft"),
+            AdjustmentReason::Found => write!(f, "Found"),
+            AdjustmentReason::CountCorrection => write!(f, "Count Correction"),
+            AdjustmentReason::Sample => write!(f, "Sample"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockAdjustment {
+    pub product_name: String,
+    pub quantity_change: i64,
+    pub reason: AdjustmentReason,
+    pub user: String,
+    pub unit_cost: f64,
+    pub timestamp: NaiveDateTime,
+}
+
+impl StockAdjustment {
+    /// Value of the stock gained (positive) or lost (negative) by this adjustment.
+    pub fn value(&self) -> f64 {
+        self.quantity_change as f64 * self.unit_cost
+    }
+}
+
+#[allow(dead_code)]
+pub trait AdjustmentManagement {
+    fn adjust_stock(
+        &mut self,
+        product_name: &str,
+        quantity_change: i64,
+        reason: AdjustmentReason,
+        user: &str,
+    ) -> Result<(), InventoryError>;
+    fn shrinkage_value(&self, reason: AdjustmentReason) -> f64;
+}
+
+impl AdjustmentManagement for Inventory {
+    fn adjust_stock(
+        &mut self,
+        product_name: &str,
+        quantity_change: i64,
+        reason: AdjustmentReason,
+        user: &str,
+    ) -> Result<(), InventoryError> {
+        if !reason.allows(quantity_change) {
+            return Err(InventoryError::InvalidInput(format!(
+                "A {} adjustment cannot change stock by {}",
+                reason, quantity_change
+            )));
+        }
+
+        let product = match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) => product,
+            None => return Err(InventoryError::ProductNotFound(product_name.to_string())),
+        };
+
+        product.quantity = match (product.quantity as i64).checked_add(quantity_change).map(u32::try_from) {
+            Some(Ok(new_quantity)) => new_quantity,
+            Some(Err(_)) if quantity_change < 0 => return Err(InventoryError::OutOfStock(product_name.to_string())),
+            _ => {
+                return Err(InventoryError::InvalidInput(format!(
+                    "the stock of '{}' cannot go above {}",
+                    product_name,
+                    u32::MAX
+                )))
+            }
+        };
+
+        let adjustment = StockAdjustment {
+            product_name: product_name.to_string(),
+            quantity_change,
+            reason,
+            user: user.to_string(),
+            unit_cost: product.price, // Cost price is taken to be the product price, as for sales
+            timestamp: self.clock.now(),
+        };
+        self.adjustments.push(adjustment);
+        Ok(())
+    }
+
+    fn shrinkage_value(&self, reason: AdjustmentReason) -> f64 {
+        -self
+            .adjustments
+            .iter()
+            .filter(|a| a.reason == reason)
+            .map(|a| a.value())
+            .sum::<f64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::inventory::InventoryManagement;
+    use chrono::NaiveDate;
+
+    fn inventory_with_product() -> Inventory {
+        let now = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(now)));
+        inventory
+            .add_product("Mug".to_string(), "Ceramic mug".to_string(), 5.0, 20)
+            .unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_adjust_stock_records_adjustment() {
+        let mut inventory = inventory_with_product();
+
+        let result = inventory.adjust_stock("Mug", -3, AdjustmentReason::Damage, "manager");
+        assert!(result.is_ok());
+        assert_eq!(inventory.products[0].quantity, 17);
+
+        let adjustment = inventory.adjustments.last().unwrap();
+        assert_eq!(adjustment.quantity_change, -3);
+        assert_eq!(adjustment.reason, AdjustmentReason::Damage);
+        assert_eq!(adjustment.user, "manager");
+        assert_eq!(adjustment.value(), -15.0);
+        assert_eq!(adjustment.timestamp.to_string(), "2024-03-15 10:00:00");
+    }
+
+    #[test]
+    fn test_adjust_stock_reason_direction() {
+        let mut inventory = inventory_with_product();
+
+        let result = inventory.adjust_stock("Mug", 2, AdjustmentReason::Theft, "manager");
+        assert_eq!(
+            result,
+            Err(InventoryError::InvalidInput("A Theft adjustment cannot change stock by 2".to_string()))
+        );
+
+        assert!(inventory.adjust_stock("Mug", 2, AdjustmentReason::Found, "manager").is_ok());
+        assert!(inventory.adjust_stock("Mug", -1, AdjustmentReason::CountCorrection, "manager").is_ok());
+        assert_eq!(inventory.products[0].quantity, 21);
+    }
+
+    #[test]
+    fn test_adjust_stock_below_zero() {
+        let mut inventory = inventory_with_product();
+
+        let result = inventory.adjust_stock("Mug", -21, AdjustmentReason::Theft, "manager");
+        assert_eq!(result, Err(InventoryError::OutOfStock("Mug".to_string())));
+        assert!(inventory.adjustments.is_empty());
+    }
+
+    #[test]
+    fn test_adjust_stock_above_largest_quantity() {
+        let mut inventory = inventory_with_product();
+        let too_large = Err(InventoryError::InvalidInput(format!("the stock of 'Mug' cannot go above {}", u32::MAX)));
+
+        assert_eq!(inventory.adjust_stock("Mug", u32::MAX as i64, AdjustmentReason::Found, "manager"), too_large);
+        assert_eq!(inventory.adjust_stock("Mug", i64::MAX, AdjustmentReason::CountCorrection, "manager"), too_large);
+        assert_eq!(inventory.products[0].quantity, 20);
+        assert!(inventory.adjustments.is_empty());
+    }
+
+    #[test]
+    fn test_shrinkage_value() {
+        let mut inventory = inventory_with_product();
+        inventory.adjust_stock("Mug", -2, AdjustmentReason::Theft, "manager").unwrap();
+        inventory.adjust_stock("Mug", -1, AdjustmentReason::Theft, "normal_user").unwrap();
+        inventory.adjust_stock("Mug", -4, AdjustmentReason::Sample, "manager").unwrap();
+
+        assert_eq!(inventory.shrinkage_value(AdjustmentReason::Theft), 15.0);
+        assert_eq!(inventory.shrinkage_value(AdjustmentReason::Sample), 20.0);
+        assert_eq!(inventory.shrinkage_value(AdjustmentReason::Damage), 0.0);
+    }
+}
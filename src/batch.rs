@@ -0,0 +1,324 @@
+use crate::cli::{self, Args, CliError};
+use crate::clock::FixedClock;
+use crate::import;
+use crate::inventory::Inventory;
+use crate::pricing::PriceManagement;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// One command of a script, with where it came from for messages.
+#[derive(Debug, PartialEq)]
+pub struct ScriptCommand {
+    pub label: String,
+    pub words: Vec<String>,
+}
+
+/// Splits a command line into words the way a shell would for simple cases:
+/// whitespace separates words, and single or double quotes keep spaces.
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("unclosed {} quote", q));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+// A JSON item is either a command line, or an object whose "command" is the
+// command and whose other keys are its options. `true` gives a bare flag.
+fn json_command(item: &serde_json::Value) -> Result<Vec<String>, String> {
+    let fields = match item {
+        serde_json::Value::String(line) => return split_words(line),
+        serde_json::Value::Object(fields) => fields,
+        _ => return Err("expected a command line or an object with a \"command\"".to_string()),
+    };
+    let mut words = match fields.get("command") {
+        Some(serde_json::Value::String(command)) => split_words(command)?,
+        _ => return Err("missing \"command\"".to_string()),
+    };
+    for (name, value) in fields.iter().filter(|(name, _)| *name != "command") {
+        match value {
+            serde_json::Value::Bool(false) | serde_json::Value::Null => {}
+            serde_json::Value::Bool(true) => words.push(format!("--{}", name)),
+            serde_json::Value::String(text) => words.push(format!("--{}={}", name, text)),
+            other => words.push(format!("--{}={}", name, other)),
+        }
+    }
+    Ok(words)
+}
+
+/// Reads a script: a JSON list when the text starts with `[`, otherwise one
+/// command per line, skipping blank lines and `#` comments.
+pub fn parse_script(text: &str) -> Result<Vec<ScriptCommand>, String> {
+    if text.trim_start().starts_with('[') {
+        let items: Vec<serde_json::Value> = serde_json::from_str(text).map_err(|e| format!("invalid JSON script: {}", e))?;
+        return items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let label = format!("item {}", index + 1);
+                json_command(item)
+                    .map(|words| ScriptCommand { label: label.clone(), words })
+                    .map_err(|e| format!("{}: {}", label, e))
+            })
+            .collect();
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let label = format!("line {}", index + 1);
+            split_words(line)
+                .map(|words| ScriptCommand { label: label.clone(), words })
+                .map_err(|e| format!("{}: {}", label, e))
+        })
+        .collect()
+}
+
+// `clock YYYY-MM-DD [HH:MM]` fixes the time recorded by the commands that
+// follow, so a script replays with the same timestamps every time.
+fn run_command(
+    words: &[String],
+    inventory: &mut Inventory,
+    user: &str,
+    out: &mut dyn Write,
+) -> Result<Option<String>, CliError> {
+    if words.first().map(String::as_str) == Some("clock") {
+        let text = words[1..].join(" ");
+        let timestamp = import::parse_timestamp(&text)
+            .map_err(|_| CliError::Usage(format!("'{}' is not a time in YYYY-MM-DD HH:MM form", text)))?;
+        inventory.clock = Box::new(FixedClock(timestamp));
+        inventory.apply_due_prices();
+        return Ok(Some(format!("Clock set to {}.", timestamp.format("%Y-%m-%d %H:%M"))));
+    }
+    let args = Args::parse(words)?;
+    if args.words.first().map(String::as_str) == Some("batch") {
+        return Err(CliError::Usage("scripts cannot run other scripts".to_string()));
+    }
+    cli::execute(&args, inventory, user, out)
+}
+
+// Reports, listings and the clock never leave a partial change behind when they fail.
+fn is_read_only(words: &[String]) -> bool {
+    match words.first().map(String::as_str) {
+        Some("report") | Some("clock") => true,
+        Some("product") => words.get(1).map(String::as_str) == Some("list"),
+        _ => false,
+    }
+}
+
+/// Runs every command of the script named on the command line against
+/// `inventory` as `user`, reporting each result, then saves to `path`.
+///
+/// A failed command is rolled back on its own. By default the script stops
+/// there and the commands before it are kept; `--continue` runs the rest.
+/// With `--transaction` the whole script is kept only if every command
+/// succeeds, so it is rolled back as a whole from a single snapshot instead.
+/// The first failure is returned so the exit code reflects it.
+pub fn run_script(
+    args: &Args,
+    inventory: &mut Inventory,
+    path: &Path,
+    user: &str,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    if args.flags.contains("stop-on-error") && args.flags.contains("continue") {
+        return Err(CliError::Usage("choose either --stop-on-error or --continue".to_string()));
+    }
+    let keep_going = args.flags.contains("continue");
+    let transaction = args.flags.contains("transaction");
+
+    let source = args.word(1, "script file")?;
+    let text = match source {
+        "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        file => fs::read_to_string(file)?,
+    };
+    let commands = parse_script(&text).map_err(CliError::Usage)?;
+
+    let start = if transaction { Some(inventory.snapshot()?) } else { None };
+    let mut changed = false;
+    let mut first_error = None;
+    let (mut succeeded, mut failed) = (0, 0);
+    for command in &commands {
+        let before = if transaction || is_read_only(&command.words) {
+            None
+        } else {
+            Some(inventory.snapshot()?)
+        };
+        let mut output = Vec::new();
+        match run_command(&command.words, inventory, user, &mut output) {
+            Ok(message) => {
+                succeeded += 1;
+                changed |= message.is_some();
+                let output = String::from_utf8_lossy(&output);
+                let shown = message.unwrap_or_default() + output.trim_end();
+                writeln!(out, "{}: {}", command.label, shown.trim_start())?;
+            }
+            Err(error) => {
+                failed += 1;
+                if let Some(before) = &before {
+                    inventory.restore(before)?;
+                }
+                writeln!(out, "{}: error: {}", command.label, error)?;
+                first_error.get_or_insert(error);
+                if !keep_going {
+                    break;
+                }
+            }
+        }
+    }
+
+    let skipped = commands.len() - succeeded - failed;
+    writeln!(
+        out,
+        "{} command(s): {} succeeded, {} failed, {} skipped.",
+        commands.len(),
+        succeeded,
+        failed,
+        skipped
+    )?;
+    if let (Some(start), Some(_)) = (&start, &first_error) {
+        inventory.restore(start)?;
+        writeln!(out, "Transaction rolled back; nothing was saved.")?;
+    } else if changed {
+        inventory.save(path)?;
+    }
+    match first_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Auth;
+
+    #[test]
+    fn test_split_words_and_parse_script() {
+        assert_eq!(
+            split_words(r#"product add --name "Desk Lamp" --sku='L 1'"#),
+            Ok(vec!["product", "add", "--name", "Desk Lamp", "--sku=L 1"].into_iter().map(String::from).collect())
+        );
+        assert!(split_words("product add --name \"Desk").is_err());
+
+        let script = parse_script("# stock up\n\nclock 2024-06-01 09:00\nsale record --product Lamp\n").unwrap();
+        assert_eq!(script.len(), 2);
+        assert_eq!(script[1].label, "line 4");
+
+        let script = parse_script(r#"[{"command": "product list", "in-stock": true, "limit": 5}, "report sales"]"#).unwrap();
+        assert_eq!(script[0].words, vec!["product", "list", "--in-stock", "--limit=5"]);
+        assert_eq!(script[1].label, "item 2");
+        assert!(parse_script("[1]").is_err());
+    }
+
+    struct Batch {
+        data: std::path::PathBuf,
+        script: std::path::PathBuf,
+    }
+
+    impl Batch {
+        fn new(name: &str, script: &str) -> Self {
+            let base = std::env::temp_dir().join(format!("inventory-batch-{}-{}", name, std::process::id()));
+            let batch = Batch {
+                data: base.with_extension("json"),
+                script: base.with_extension("txt"),
+            };
+            let _ = fs::remove_file(&batch.data);
+            fs::write(&batch.script, script).unwrap();
+            batch
+        }
+
+        fn run(&self, options: &str) -> (Result<(), CliError>, String) {
+            let mut args = vec!["batch".to_string(), self.script.display().to_string()];
+            args.extend(options.split_whitespace().map(str::to_string));
+            let data = self.data.display().to_string();
+            let env = |name: &str| match name {
+                "INVENTORY_USER" => Some("manager".to_string()),
+                "INVENTORY_PASSWORD" => Some("password123".to_string()),
+                "INVENTORY_DATA" => Some(data.clone()),
+                _ => None,
+            };
+            let mut out = Vec::new();
+            let result = cli::run(&args, &Auth::new(), &env, &mut out);
+            (result, String::from_utf8(out).unwrap())
+        }
+
+        fn inventory(&self) -> Inventory {
+            Inventory::load(&self.data).unwrap()
+        }
+    }
+
+    impl Drop for Batch {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.data);
+            let _ = fs::remove_file(&self.script);
+        }
+    }
+
+    const SCRIPT: &str = "clock 2024-06-01 09:00
+product add --name 'Desk Lamp' --price 20 --quantity 3 --sku LMP-1
+sale record --product LMP-1 --quantity 5
+sale record --product LMP-1 --quantity 1
+";
+
+    #[test]
+    fn test_stop_on_error_keeps_earlier_commands() {
+        let batch = Batch::new("stop", SCRIPT);
+
+        let (result, output) = batch.run("");
+        assert_eq!(result.unwrap_err().exit_code(), 6);
+        assert!(output.contains("line 3: error: Inventory Error: Not enough stock of Desk Lamp"), "{}", output);
+        assert!(output.contains("4 command(s): 2 succeeded, 1 failed, 1 skipped."), "{}", output);
+        let inventory = batch.inventory();
+        assert_eq!(inventory.products[0].quantity, 3);
+        assert!(inventory.sales.is_empty());
+    }
+
+    #[test]
+    fn test_clock_command() {
+        let mut inventory = Inventory::new();
+        let words = |line: &str| split_words(line).unwrap();
+
+        let message = run_command(&words("clock 2024-06-01T09:30"), &mut inventory, "", &mut Vec::new()).unwrap();
+        assert_eq!(message, Some("Clock set to 2024-06-01 09:30.".to_string()));
+        let error = run_command(&words("clock tomorrow"), &mut inventory, "", &mut Vec::new()).unwrap_err();
+        assert_eq!(error.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_continue_and_transaction() {
+        let batch = Batch::new("continue", SCRIPT);
+
+        let (result, output) = batch.run("--continue --transaction");
+        assert!(result.is_err());
+        assert!(output.contains("3 succeeded, 1 failed, 0 skipped."), "{}", output);
+        assert!(output.contains("Transaction rolled back"), "{}", output);
+        assert!(batch.inventory().products.is_empty());
+
+        let (result, _) = batch.run("--continue");
+        assert!(result.is_err());
+        let inventory = batch.inventory();
+        assert_eq!(inventory.products[0].quantity, 2);
+        assert_eq!(inventory.sales[0].timestamp.to_string(), "2024-06-01 09:00:00");
+    }
+}
@@ -0,0 +1,323 @@
+use crate::inventory::{Inventory, InventoryError, Product};
+use crate::pricing::PriceManagement;
+
+/// A price reduction on the whole basket.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Discount {
+    #[default]
+    None,
+    Percent(f64),
+    Amount(f64),
+}
+
+pub struct BasketLine {
+    pub product_name: String,
+    pub quantity: u32,
+    pub unit_price: f64,
+}
+
+impl BasketLine {
+    pub fn total(&self) -> f64 {
+        self.unit_price * self.quantity as f64
+    }
+}
+
+/// The products being rung up at the checkout. Nothing changes in the inventory
+/// until the basket is completed with `Checkout::complete_checkout`.
+#[derive(Default)]
+pub struct Basket {
+    pub lines: Vec<BasketLine>,
+    pub discount: Discount,
+}
+
+fn round_to_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+impl Basket {
+    /// Adds `quantity` of the product whose SKU, barcode or name is `code`, at
+    /// its current price, which may be a sale price. Scanning a product already in the basket adds to its line.
+    pub fn add(&mut self, inventory: &Inventory, code: &str, quantity: u32) -> Result<usize, InventoryError> {
+        if quantity == 0 {
+            return Err(InventoryError::InvalidQuantity(quantity));
+        }
+        let product = match inventory.find_product_by_code(code) {
+            Some(product) => product,
+            None => return Err(InventoryError::ProductNotFound(code.to_string())),
+        };
+        let index = match self.lines.iter().position(|l| l.product_name == product.name) {
+            Some(index) => index,
+            None => {
+                self.lines.push(BasketLine {
+                    product_name: product.name.clone(),
+                    quantity: 0,
+                    unit_price: inventory.current_price(&product.name).unwrap_or(product.price),
+                });
+                self.lines.len() - 1
+            }
+        };
+        let wanted = self.lines[index].quantity + quantity;
+        if wanted > product.quantity {
+            if self.lines[index].quantity == 0 {
+                self.lines.remove(index);
+            }
+            return Err(InventoryError::OutOfStock(product.name.clone()));
+        }
+        self.lines[index].quantity = wanted;
+        Ok(index)
+    }
+
+    fn line_mut(&mut self, line: usize) -> Result<&mut BasketLine, InventoryError> {
+        let count = self.lines.len();
+        self.lines
+            .get_mut(line)
+            .ok_or_else(|| InventoryError::InvalidInput(format!("line {} is not in the basket of {} line(s)", line + 1, count)))
+    }
+
+    pub fn set_quantity(&mut self, inventory: &Inventory, line: usize, quantity: u32) -> Result<(), InventoryError> {
+        if quantity == 0 {
+            return Err(InventoryError::InvalidQuantity(quantity));
+        }
+        let line = self.line_mut(line)?;
+        let on_hand = inventory.products.iter().find(|p| p.name == line.product_name).map_or(0, |p| p.quantity);
+        if quantity > on_hand {
+            return Err(InventoryError::OutOfStock(line.product_name.clone()));
+        }
+        line.quantity = quantity;
+        Ok(())
+    }
+
+    pub fn set_price(&mut self, line: usize, unit_price: f64) -> Result<(), InventoryError> {
+        if unit_price <= 0.0 {
+            return Err(InventoryError::InvalidPrice(unit_price));
+        }
+        self.line_mut(line)?.unit_price = unit_price;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, line: usize) -> Result<BasketLine, InventoryError> {
+        self.line_mut(line)?;
+        Ok(self.lines.remove(line))
+    }
+
+    pub fn set_discount(&mut self, discount: Discount) -> Result<(), InventoryError> {
+        match discount {
+            Discount::Percent(percent) if !(0.0..100.0).contains(&percent) => Err(InventoryError::InvalidInput(
+                format!("a percentage discount must be at least 0 and below 100, got {}", percent),
+            )),
+            Discount::Amount(amount) if amount < 0.0 => {
+                Err(InventoryError::InvalidInput(format!("a discount cannot be negative, got {}", amount)))
+            }
+            _ => {
+                self.discount = discount;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn subtotal(&self) -> f64 {
+        self.lines.iter().map(BasketLine::total).sum()
+    }
+
+    /// The discount in money, never more than the subtotal.
+    pub fn discount_amount(&self) -> f64 {
+        let amount = match self.discount {
+            Discount::None => 0.0,
+            Discount::Percent(percent) => round_to_cents(self.subtotal() * percent / 100.0),
+            Discount::Amount(amount) => amount,
+        };
+        amount.min(self.subtotal())
+    }
+
+    pub fn total(&self) -> f64 {
+        round_to_cents(self.subtotal() - self.discount_amount())
+    }
+}
+
+pub struct Receipt {
+    pub receipt_id: u32,
+    pub total: f64,
+    pub tendered: f64,
+    pub change: f64,
+}
+
+#[allow(dead_code)]
+pub trait Checkout {
+    fn find_product_by_code(&self, code: &str) -> Option<&Product>;
+    fn complete_checkout(&mut self, basket: &Basket, tendered: f64) -> Result<Receipt, InventoryError>;
+}
+
+impl Checkout for Inventory {
+    // SKUs are matched exactly so barcodes scan reliably; names are a fallback
+    // for products without one. Archived products are never found.
+    fn find_product_by_code(&self, code: &str) -> Option<&Product> {
+        let code = code.trim();
+        let mut active = self.products.iter().filter(|p| !p.archived);
+        active
+            .clone()
+            .find(|p| !p.sku.is_empty() && p.sku == code)
+            .or_else(|| active.find(|p| p.name.eq_ignore_ascii_case(code)))
+    }
+
+    // Every line is checked before any is recorded, so a failed checkout leaves
+    // the inventory untouched. The discount is spread over the lines in
+    // proportion to their value, so each sale records the price actually paid.
+    // Line totals are rounded to the cent, with the last line taking whatever
+    // rounding left over, so they add up to the receipt total.
+    fn complete_checkout(&mut self, basket: &Basket, tendered: f64) -> Result<Receipt, InventoryError> {
+        if basket.lines.is_empty() {
+            return Err(InventoryError::SaleFailed("The basket is empty".to_string()));
+        }
+        let total = basket.total();
+        if total <= 0.0 {
+            return Err(InventoryError::SaleFailed("The discount cannot cover the whole basket".to_string()));
+        }
+        if tendered < total {
+            return Err(InventoryError::SaleFailed(format!(
+                "Tendered ${:.2} is less than the total of ${:.2}",
+                tendered, total
+            )));
+        }
+        for line in &basket.lines {
+            match self.products.iter().find(|p| p.name == line.product_name) {
+                Some(product) if product.archived => {
+                    return Err(InventoryError::SaleFailed(format!("'{}' is archived", line.product_name)))
+                }
+                Some(product) if product.quantity < line.quantity => {
+                    return Err(InventoryError::OutOfStock(line.product_name.clone()))
+                }
+                Some(_) => {}
+                None => return Err(InventoryError::ProductNotFound(line.product_name.clone())),
+            }
+        }
+
+        let paid_share = total / basket.subtotal();
+        let mut line_totals: Vec<f64> = basket.lines.iter().map(|line| round_to_cents(line.total() * paid_share)).collect();
+        let rounded: f64 = line_totals[..line_totals.len() - 1].iter().sum();
+        *line_totals.last_mut().unwrap() = round_to_cents(total - rounded);
+        if let Some((line, _)) = basket.lines.iter().zip(&line_totals).find(|(_, &line_total)| line_total <= 0.0) {
+            return Err(InventoryError::SaleFailed(format!(
+                "The discount leaves nothing to pay for {}",
+                line.product_name
+            )));
+        }
+
+        let receipt_id = self.next_receipt_id();
+        for (line, line_total) in basket.lines.iter().zip(line_totals) {
+            self.sell(&line.product_name, line.quantity, line_total / line.quantity as f64, line_total, receipt_id)?;
+        }
+        Ok(Receipt {
+            receipt_id,
+            total,
+            tendered,
+            change: round_to_cents(tendered - total),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+
+    fn inventory_with_shelf() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Coffee".to_string(), "Ground, 250g".to_string(), 6.0, 10).unwrap();
+        inventory.add_product("Milk".to_string(), "1 litre".to_string(), 1.5, 2).unwrap();
+        inventory.set_product_sku("Coffee", "4006381333931".to_string()).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_basket_scanning_and_edits() {
+        let inventory = inventory_with_shelf();
+        let mut basket = Basket::default();
+
+        assert_eq!(basket.add(&inventory, "4006381333931", 1), Ok(0));
+        assert_eq!(basket.add(&inventory, "4006381333931", 2), Ok(0));
+        assert_eq!(basket.add(&inventory, "milk", 1), Ok(1));
+        assert_eq!(basket.lines[0].quantity, 3);
+        assert_eq!(basket.subtotal(), 19.5);
+
+        assert_eq!(basket.add(&inventory, "Milk", 2), Err(InventoryError::OutOfStock("Milk".to_string())));
+        assert_eq!(basket.add(&inventory, "123", 1), Err(InventoryError::ProductNotFound("123".to_string())));
+        basket.set_quantity(&inventory, 1, 2).unwrap();
+        basket.set_price(0, 5.0).unwrap();
+        assert_eq!(basket.subtotal(), 18.0);
+        assert_eq!(basket.remove(1).unwrap().product_name, "Milk");
+        assert!(basket.remove(1).is_err());
+    }
+
+    #[test]
+    fn test_discounts() {
+        let inventory = inventory_with_shelf();
+        let mut basket = Basket::default();
+        basket.add(&inventory, "Coffee", 2).unwrap();
+
+        basket.set_discount(Discount::Percent(10.0)).unwrap();
+        assert_eq!(basket.total(), 10.8);
+        basket.set_discount(Discount::Amount(50.0)).unwrap();
+        assert_eq!(basket.total(), 0.0);
+        assert!(basket.set_discount(Discount::Percent(100.0)).is_err());
+        assert!(basket.set_discount(Discount::Amount(-1.0)).is_err());
+    }
+
+    #[test]
+    fn test_complete_checkout() {
+        let mut inventory = inventory_with_shelf();
+        inventory.record_sale("Coffee", 1, 6.0).unwrap();
+        let mut basket = Basket::default();
+        basket.add(&inventory, "Coffee", 2).unwrap();
+        basket.add(&inventory, "Milk", 2).unwrap();
+        basket.set_discount(Discount::Amount(3.0)).unwrap();
+
+        assert!(matches!(inventory.complete_checkout(&basket, 10.0), Err(InventoryError::SaleFailed(_))));
+        let receipt = inventory.complete_checkout(&basket, 20.0).unwrap();
+
+        assert_eq!((receipt.total, receipt.change), (12.0, 8.0));
+        assert_eq!(receipt.receipt_id, 2);
+        let sales: Vec<_> = inventory.sales.iter().filter(|s| s.receipt_id == 2).collect();
+        assert_eq!(sales.len(), 2);
+        assert!((sales.iter().map(|s| s.total_sale).sum::<f64>() - 12.0).abs() < 1e-9);
+        assert_eq!((inventory.products[0].quantity, inventory.products[1].quantity), (7, 0));
+
+        // The milk is sold out, so the same basket cannot be sold again.
+        assert_eq!(
+            inventory.complete_checkout(&basket, 20.0).err(),
+            Some(InventoryError::OutOfStock("Milk".to_string()))
+        );
+        assert_eq!(inventory.sales.len(), 3);
+    }
+
+    #[test]
+    fn test_checkout_lines_add_up_to_total() {
+        let mut inventory = inventory_with_shelf();
+        let mut basket = Basket::default();
+        basket.add(&inventory, "Coffee", 1).unwrap();
+        basket.add(&inventory, "Milk", 2).unwrap();
+        basket.set_discount(Discount::Amount(1.0)).unwrap();
+
+        let receipt = inventory.complete_checkout(&basket, 10.0).unwrap();
+
+        let totals: Vec<f64> = inventory.sales.iter().map(|s| s.total_sale).collect();
+        assert_eq!(totals, vec![5.33, 2.67]);
+        assert_eq!(totals.iter().sum::<f64>(), receipt.total);
+    }
+
+    #[test]
+    fn test_checkout_refuses_product_archived_after_scanning() {
+        let mut inventory = inventory_with_shelf();
+        let mut basket = Basket::default();
+        basket.add(&inventory, "Coffee", 1).unwrap();
+        basket.add(&inventory, "Milk", 1).unwrap();
+        inventory.archive_product("Milk").unwrap();
+
+        assert_eq!(
+            inventory.complete_checkout(&basket, 10.0).err(),
+            Some(InventoryError::SaleFailed("'Milk' is archived".to_string()))
+        );
+        assert!(inventory.sales.is_empty());
+        assert_eq!(inventory.products[0].quantity, 10);
+    }
+}
@@ -0,0 +1,586 @@
+use crate::abc::AbcClass;
+use crate::auth::{Auth, AuthError};
+use crate::batch;
+use crate::checkout::Checkout;
+use crate::clock::DateRange;
+use crate::input;
+use crate::inventory::{Inventory, InventoryError, InventoryManagement};
+use crate::pricing::PriceManagement;
+use crate::purchases::PurchaseManagement;
+use crate::render::{ExportFormat, ReportRenderer, TextRenderer};
+use crate::reporting::{Grouping, Report, ReportTable, Reporting, Value};
+use crate::sales::SalesManagement;
+use crate::search::{ProductFilter, ProductSearch};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: inventory [--data FILE] [--credentials FILE] COMMAND
+       inventory [--data FILE]    (starts the interactive menus)
+
+Commands:
+  product add --name NAME --price PRICE --quantity QTY [--description TEXT]
+              [--category NAME] [--sku CODE] [--reorder-level QTY]
+  product edit NAME [--name NAME] [--description TEXT] [--price PRICE] [--quantity QTY]
+              [--category NAME] [--sku CODE] [--reorder-level QTY]
+  product delete NAME
+              Only products without transactions can be deleted; archive the others.
+  product archive NAME
+  product restore NAME
+  product list [--search TEXT] [--in-stock] [--below-reorder] [--category NAME]
+               [--min-price PRICE] [--max-price PRICE] [--archived] [--format FORMAT]
+  sale record --product NAME_OR_SKU --quantity QTY [--price PRICE]
+  purchase record --product NAME --quantity QTY --price PRICE [--supplier NAME]
+  price schedule --product NAME --price PRICE --from DATE [--to DATE]
+              Without --to the price changes for good on DATE; with --to it is a
+              sale price from DATE until the end of the --to date.
+  report KIND [--from DATE] [--to DATE] [--format FORMAT] [--class A|B|C]
+              [--by product|category] [--date DATE] [--limit N] [--threshold PERCENT] [--days N]
+  batch FILE [--stop-on-error | --continue] [--transaction]
+              Runs the commands in FILE (one per line, or a JSON list); - reads standard input.
+
+Report kinds: inventory, sales, purchases, adjustments, profit-and-loss, valuation,
+top-sellers, slow-movers, dead-stock, price-history [--product NAME]. Formats: text (default), csv, json, markdown, html.
+
+Credentials come from --credentials or INVENTORY_CREDENTIALS (a file with
+username= and password= lines), or from INVENTORY_USER and INVENTORY_PASSWORD.
+Data is kept in --data, INVENTORY_DATA or inventory.json.";
+
+/// Options that take no value.
+const FLAGS: [&str; 6] = ["in-stock", "below-reorder", "archived", "stop-on-error", "continue", "transaction"];
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("{0}\nRun with --help for usage.")]
+    Usage(String),
+
+    #[error("Authentication Error: {0}")]
+    Auth(#[from] AuthError),
+
+    #[error("Inventory Error: {0}")]
+    Inventory(#[from] InventoryError),
+
+    #[error("IO Error: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl CliError {
+    /// The process exit code, so scripts can tell kinds of failure apart.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Auth(_) => 3,
+            CliError::Inventory(error) => match error {
+                InventoryError::ProductNotFound(_) => 4,
+                InventoryError::InvalidQuantity(_)
+                | InventoryError::InvalidPrice(_)
+                | InventoryError::InvalidInput(_)
+                | InventoryError::DuplicateProduct(_)
+                | InventoryError::SkuInUse(..)
+                | InventoryError::ProductHasTransactions(_) => 5,
+                InventoryError::OutOfStock(_) => 6,
+                InventoryError::PurchaseFailed(_)
+                | InventoryError::SaleFailed(_)
+                | InventoryError::ReturnFailed(_)
+                | InventoryError::SupplierReturnFailed(_)
+                | InventoryError::StockTakeFailed(_)
+                | InventoryError::UndoFailed(_) => 7,
+                InventoryError::StorageFailed(_) => 8,
+            },
+            CliError::Io(_) => 8,
+        }
+    }
+}
+
+/// Command-line words split into positional words, `--name value` options
+/// (also written `--name=value`) and bare flags.
+pub(crate) struct Args {
+    pub(crate) words: Vec<String>,
+    options: HashMap<String, String>,
+    pub(crate) flags: HashSet<String>,
+}
+
+impl Args {
+    pub(crate) fn parse(args: &[String]) -> Result<Args, CliError> {
+        let mut parsed = Args {
+            words: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix("--") {
+                Some(name) => name,
+                None => {
+                    parsed.words.push(arg.clone());
+                    continue;
+                }
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                parsed.options.insert(name.to_string(), value.to_string());
+            } else if FLAGS.contains(&name) || name == "help" {
+                parsed.flags.insert(name.to_string());
+            } else {
+                match args.next() {
+                    Some(value) => parsed.options.insert(name.to_string(), value.clone()),
+                    None => return Err(CliError::Usage(format!("--{} needs a value", name))),
+                };
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub(crate) fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.option(name)
+            .ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+
+    fn parsed<T>(&self, name: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, CliError> {
+        self.option(name)
+            .map(|text| parse(text).map_err(|message| CliError::Usage(format!("--{}: {}", name, message))))
+            .transpose()
+    }
+
+    fn required_parsed<T>(&self, name: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, CliError> {
+        self.parsed(name, parse)?
+            .ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+
+    pub(crate) fn word(&self, index: usize, what: &str) -> Result<&str, CliError> {
+        self.words
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| CliError::Usage(format!("missing {}", what)))
+    }
+}
+
+fn parse_class(text: &str) -> Result<AbcClass, String> {
+    AbcClass::ALL
+        .into_iter()
+        .find(|c| c.to_string().eq_ignore_ascii_case(text))
+        .ok_or_else(|| format!("'{}' is not an ABC class.", text))
+}
+
+/// Reads `username=` and `password=` lines; blank lines and `#` comments are ignored.
+fn read_credentials_file(path: &str) -> Result<(String, String), CliError> {
+    let text = fs::read_to_string(path)?;
+    let mut username = None;
+    let mut password = None;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        match line.split_once('=').map(|(key, value)| (key.trim(), value.trim().to_string())) {
+            Some(("username", value)) => username = Some(value),
+            Some(("password", value)) => password = Some(value),
+            _ => {}
+        }
+    }
+    match (username, password) {
+        (Some(username), Some(password)) => Ok((username, password)),
+        _ => Err(CliError::Usage(format!("{} needs username= and password= lines", path))),
+    }
+}
+
+fn credentials(args: &Args, env: &dyn Fn(&str) -> Option<String>) -> Result<(String, String), CliError> {
+    if let Some(path) = args.option("credentials").map(str::to_string).or_else(|| env("INVENTORY_CREDENTIALS")) {
+        return read_credentials_file(&path);
+    }
+    match (env("INVENTORY_USER"), env("INVENTORY_PASSWORD")) {
+        (Some(username), Some(password)) => Ok((username, password)),
+        _ => Err(CliError::Usage(
+            "no credentials: use --credentials FILE or set INVENTORY_USER and INVENTORY_PASSWORD".to_string(),
+        )),
+    }
+}
+
+/// Where the inventory is kept between runs: `--data`, then `INVENTORY_DATA`,
+/// then `inventory.json` in the current directory.
+fn data_path(args: &Args, env: &dyn Fn(&str) -> Option<String>) -> PathBuf {
+    PathBuf::from(
+        args.option("data")
+            .map(str::to_string)
+            .or_else(|| env("INVENTORY_DATA"))
+            .unwrap_or_else(|| "inventory.json".to_string()),
+    )
+}
+
+/// The data file for the interactive interface, which starts when no command
+/// is given (`--data` alone is allowed). `None` means the arguments are for `run`.
+pub fn interactive_data_path(args: &[String], env: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    match Args::parse(args) {
+        Ok(parsed) if parsed.words.is_empty() && parsed.flags.is_empty() && parsed.options.keys().all(|k| k == "data") => {
+            Some(data_path(&parsed, env))
+        }
+        _ => None,
+    }
+}
+
+/// Runs one command and returns once it is done. Commands that change the
+/// inventory save it back to the data file; nothing is saved when they fail.
+pub fn run(args: &[String], auth: &Auth, env: &dyn Fn(&str) -> Option<String>, out: &mut dyn Write) -> Result<(), CliError> {
+    let args = Args::parse(args)?;
+    if args.words.is_empty() || args.flags.contains("help") || args.words[0] == "help" {
+        writeln!(out, "{}", USAGE)?;
+        return Ok(());
+    }
+
+    let (username, password) = credentials(&args, env)?;
+    auth.authenticate(&username, &password)?;
+
+    let path = data_path(&args, env);
+    let mut inventory = Inventory::load(&path)?;
+    inventory.apply_due_prices();
+
+    if args.words[0] == "batch" {
+        return batch::run_script(&args, &mut inventory, &path, &username, out);
+    }
+    if let Some(message) = execute(&args, &mut inventory, &username, out)? {
+        inventory.save(&path)?;
+        writeln!(out, "{}", message)?;
+    }
+    Ok(())
+}
+
+/// Applies one command to `inventory`. Commands that change it return the
+/// message to show once the change is saved; reports and listings write to
+/// `out` and return `None`.
+pub(crate) fn execute(
+    args: &Args,
+    inventory: &mut Inventory,
+    user: &str,
+    out: &mut dyn Write,
+) -> Result<Option<String>, CliError> {
+    let command = (args.word(0, "command")?, args.words.get(1).map(String::as_str));
+    let message = match command {
+        ("product", Some("add")) => add_product(args, inventory)?,
+        ("product", Some("edit")) => edit_product(args, inventory, user)?,
+        ("product", Some("delete")) => {
+            let name = args.word(2, "product name")?;
+            inventory.delete_product(name)?;
+            format!("Deleted {}.", name)
+        }
+        ("product", Some("archive")) => {
+            let name = args.word(2, "product name")?;
+            inventory.archive_product(name)?;
+            format!("Archived {}.", name)
+        }
+        ("product", Some("restore")) => {
+            let name = args.word(2, "product name")?;
+            inventory.restore_product(name)?;
+            format!("Restored {}.", name)
+        }
+        ("product", Some("list")) => return list_products(args, inventory, out).map(|()| None),
+        ("sale", Some("record")) => record_sale(args, inventory)?,
+        ("purchase", Some("record")) => {
+            let name = args.required("product")?;
+            let quantity = args.required_parsed("quantity", input::parse_whole_number)?;
+            let price = args.required_parsed("price", input::parse_money)?;
+            inventory.record_purchase_from(args.option("supplier").unwrap_or(""), name, quantity, price)?;
+            format!("Received {} x {}.", quantity, name)
+        }
+        ("price", Some("schedule")) => schedule_price(args, inventory, user)?,
+        ("report", _) => return report(args, inventory, out).map(|()| None),
+        _ => return Err(CliError::Usage(format!("unknown command '{}'", args.words.join(" ")))),
+    };
+    Ok(Some(message))
+}
+
+fn add_product(args: &Args, inventory: &mut Inventory) -> Result<String, CliError> {
+    let name = args.required("name")?.to_string();
+    let price = args.required_parsed("price", input::parse_money)?;
+    let quantity = args.required_parsed("quantity", input::parse_whole_number)?;
+    let reorder_level = args.parsed("reorder-level", input::parse_whole_number)?.unwrap_or(0);
+    inventory.check_sku_available(&name, args.option("sku").unwrap_or(""))?;
+    inventory.add_product(name.clone(), args.option("description").unwrap_or("").to_string(), price, quantity)?;
+    inventory.set_product_category(&name, args.option("category").unwrap_or("").to_string())?;
+    inventory.set_product_sku(&name, args.option("sku").unwrap_or("").to_string())?;
+    inventory.set_reorder_level(&name, reorder_level)?;
+    Ok(format!("Added {}.", name))
+}
+
+// Only the options given are changed.
+fn edit_product(args: &Args, inventory: &mut Inventory, user: &str) -> Result<String, CliError> {
+    let original = args.word(2, "product name")?;
+    let name = args.option("name").unwrap_or(original).to_string();
+    inventory.check_sku_available(original, args.option("sku").unwrap_or(""))?;
+    inventory.edit_product(
+        original,
+        args.option("name").map(str::to_string),
+        args.option("description").map(str::to_string),
+        args.parsed("price", input::parse_money)?,
+        args.parsed("quantity", input::parse_whole_number)?,
+        user,
+    )?;
+    if let Some(category) = args.option("category") {
+        inventory.set_product_category(&name, category.to_string())?;
+    }
+    if let Some(sku) = args.option("sku") {
+        inventory.set_product_sku(&name, sku.to_string())?;
+    }
+    if let Some(level) = args.parsed("reorder-level", input::parse_whole_number)? {
+        inventory.set_reorder_level(&name, level)?;
+    }
+    Ok(format!("Updated {}.", name))
+}
+
+// The price defaults to the price in effect now, as at the checkout.
+fn record_sale(args: &Args, inventory: &mut Inventory) -> Result<String, CliError> {
+    let code = args.required("product")?;
+    let name = match inventory.find_product_by_code(code) {
+        Some(product) => product.name.clone(),
+        None => return Err(InventoryError::ProductNotFound(code.to_string()).into()),
+    };
+    let quantity = args.required_parsed("quantity", input::parse_whole_number)?;
+    match args.parsed("price", input::parse_money)? {
+        Some(price) => inventory.record_sale(&name, quantity, price)?,
+        None => {
+            inventory.record_sale_at_current_price(&name, quantity)?;
+        }
+    }
+    Ok(format!("Sold {} x {}.", quantity, name))
+}
+
+// Dates are whole days: a sale price runs to the end of its --to date.
+fn schedule_price(args: &Args, inventory: &mut Inventory, user: &str) -> Result<String, CliError> {
+    let name = args.required("product")?;
+    let price = args.required_parsed("price", input::parse_money)?;
+    let from = match args.parsed("from", input::parse_date)? {
+        Some(date) => date,
+        None => return Err(CliError::Usage("missing --from".to_string())),
+    };
+    let to = args.parsed("to", input::parse_date)?;
+    let until = to.and_then(|date| date.succ_opt()).map(|date| date.and_time(Default::default()));
+    inventory.schedule_price(name, price, from.and_time(Default::default()), until, user)?;
+    Ok(match to {
+        Some(to) => format!("{} is on sale at ${:.2} from {} to {}.", name, price, from, to),
+        None => format!("{} changes to ${:.2} on {}.", name, price, from),
+    })
+}
+
+fn render(args: &Args, report: &Report, out: &mut dyn Write) -> Result<(), CliError> {
+    let rendered = match args.option("format").unwrap_or("text") {
+        "text" => TextRenderer.render(report),
+        name => match ExportFormat::from_name(name) {
+            Some(format) => format.renderer().render(report),
+            None => return Err(CliError::Usage(format!("unknown format '{}'", name))),
+        },
+    };
+    write!(out, "{}", rendered)?;
+    Ok(())
+}
+
+fn list_products(args: &Args, inventory: &Inventory, out: &mut dyn Write) -> Result<(), CliError> {
+    let filter = ProductFilter {
+        query: args.option("search").unwrap_or("").to_string(),
+        in_stock_only: args.flags.contains("in-stock"),
+        below_reorder_level: args.flags.contains("below-reorder"),
+        category: args.option("category").map(str::to_string),
+        min_price: args.parsed("min-price", input::parse_money)?,
+        max_price: args.parsed("max-price", input::parse_money)?,
+        include_archived: args.flags.contains("archived"),
+    };
+    let mut table = ReportTable::new(
+        "Products",
+        &["Name", "SKU", "Category", "Quantity", "Price", "Reorder Level", "Archived"],
+        "No products match.",
+    );
+    for product in inventory.search_products(&filter) {
+        table.rows.push(vec![
+            product.name.as_str().into(),
+            product.sku.as_str().into(),
+            product.category.as_str().into(),
+            product.quantity.into(),
+            Value::Money(product.price),
+            product.reorder_level.into(),
+            if product.archived { "yes" } else { "" }.into(),
+        ]);
+    }
+    let mut report = Report::new("Products");
+    report.tables.push(table);
+    render(args, &report, out)
+}
+
+fn report(args: &Args, inventory: &Inventory, out: &mut dyn Write) -> Result<(), CliError> {
+    let all = DateRange::all();
+    let range = DateRange::new(
+        args.parsed("from", input::parse_date)?.unwrap_or(all.from),
+        args.parsed("to", input::parse_date)?.unwrap_or(all.to),
+    );
+    let class = args.parsed("class", parse_class)?;
+    let report = match args.word(1, "report kind")? {
+        "inventory" => inventory.generate_inventory_report(class),
+        "sales" => inventory.generate_sales_report(&range),
+        "purchases" => inventory.generate_purchase_report(&range),
+        "adjustments" => inventory.generate_adjustment_report(),
+        "profit-and-loss" => {
+            let grouping = match args.option("by").unwrap_or("product") {
+                "product" => Grouping::Product,
+                "category" => Grouping::Category,
+                other => return Err(CliError::Usage(format!("--by: '{}' is not product or category", other))),
+            };
+            inventory.generate_profit_and_loss_report(&range, grouping)
+        }
+        "valuation" => {
+            let as_of = args.parsed("date", input::parse_date)?.unwrap_or_else(|| inventory.clock.now().date());
+            inventory.generate_valuation_report(as_of, class)
+        }
+        "top-sellers" => {
+            let limit = args.parsed("limit", input::parse_whole_number)?.unwrap_or(10);
+            inventory.generate_top_sellers_report(&range, limit)
+        }
+        "slow-movers" => {
+            let threshold = args.parsed("threshold", input::parse_decimal)?.unwrap_or(25.0);
+            inventory.generate_slow_movers_report(&range, threshold, class)
+        }
+        "dead-stock" => {
+            let days = args.parsed("days", input::parse_whole_number)?.unwrap_or(90);
+            inventory.generate_dead_stock_report(days, class)
+        }
+        "price-history" => inventory.generate_price_history_report(args.option("product")),
+        other => return Err(CliError::Usage(format!("unknown report '{}'", other))),
+    };
+    render(args, &report, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Session {
+        data: PathBuf,
+    }
+
+    impl Session {
+        fn new(name: &str) -> Self {
+            let data = std::env::temp_dir().join(format!("inventory-cli-{}-{}.json", name, std::process::id()));
+            let _ = fs::remove_file(&data);
+            Session { data }
+        }
+
+        fn run(&self, command: &str) -> Result<String, CliError> {
+            let data = self.data.display().to_string();
+            let env = |name: &str| match name {
+                "INVENTORY_USER" => Some("manager".to_string()),
+                "INVENTORY_PASSWORD" => Some("password123".to_string()),
+                "INVENTORY_DATA" => Some(data.clone()),
+                _ => None,
+            };
+            let args: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+            let mut out = Vec::new();
+            run(&args, &Auth::new(), &env, &mut out)?;
+            Ok(String::from_utf8(out).unwrap())
+        }
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.data);
+        }
+    }
+
+    #[test]
+    fn test_commands_persist_between_runs() {
+        let session = Session::new("persist");
+
+        assert_eq!(
+            session.run("product add --name Lamp --price 20 --quantity 5 --sku LMP-1").unwrap(),
+            "Added Lamp.\n"
+        );
+        assert_eq!(session.run("sale record --product LMP-1 --quantity 2").unwrap(), "Sold 2 x Lamp.\n");
+        session.run("product edit Lamp --reorder-level=4").unwrap();
+
+        let listing = session.run("product list --below-reorder --format csv").unwrap();
+        assert!(listing.contains("Lamp,LMP-1,,3,20.00,4"), "{}", listing);
+        let report = session.run("report sales --format json").unwrap();
+        assert!(report.contains("\"Total Sale\": 40.0"), "{}", report);
+    }
+
+    #[test]
+    fn test_sale_prices() {
+        let session = Session::new("sale-prices");
+        session.run("product add --name Lamp --price 20 --quantity 5").unwrap();
+
+        assert_eq!(
+            session.run("price schedule --product Lamp --price 15 --from 2020-01-01 --to 2099-12-31").unwrap(),
+            "Lamp is on sale at $15.00 from 2020-01-01 to 2099-12-31.\n"
+        );
+        assert_eq!(session.run("price schedule --product Lamp --price 25 --from 2020-01-01").unwrap_err().exit_code(), 5);
+        session.run("sale record --product Lamp --quantity 1").unwrap();
+        let report = session.run("report sales --format csv").unwrap();
+        assert!(report.contains("Lamp,1,15.00"), "{}", report);
+        let history = session.run("report price-history --product Lamp --format csv").unwrap();
+        assert!(history.contains("Lamp,Sale Price,20.00,15.00"), "{}", history);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let session = Session::new("exit-codes");
+        session.run("product add --name Lamp --price 20 --quantity 1").unwrap();
+
+        let code = |command: &str| session.run(command).unwrap_err().exit_code();
+        assert_eq!(code("product frobnicate"), 2);
+        assert_eq!(code("product add --name Mug --price"), 2);
+        assert_eq!(code("sale record --product Mug --quantity 1"), 4);
+        assert_eq!(code("product add --name Mug --price 3 --quantity 0"), 5);
+        assert_eq!(code("sale record --product Lamp --quantity 2"), 6);
+        session.run("sale record --product Lamp --quantity 1").unwrap();
+        assert_eq!(code("product delete Lamp"), 5);
+        assert_eq!(session.run("product archive Lamp").unwrap(), "Archived Lamp.\n");
+        assert_eq!(code("sale record --product Lamp --quantity 1"), 4);
+        assert!(session.run("product list --archived --format csv").unwrap().contains("Lamp,,,0,20.00,0,yes"));
+        assert_eq!(code("report sales --from yesterday"), 2);
+    }
+
+    #[test]
+    fn test_missing_required_options_are_usage_errors() {
+        let session = Session::new("missing-options");
+        session.run("product add --name Lamp --price 20 --quantity 1").unwrap();
+
+        let error = session.run("product add --name Mug --quantity 3").unwrap_err();
+        assert_eq!(error.to_string(), CliError::Usage("--price is required".to_string()).to_string());
+        assert_eq!(error.exit_code(), 2);
+        let code = |command: &str| session.run(command).unwrap_err().exit_code();
+        assert_eq!(code("product add --name Mug --price 3"), 2);
+        assert_eq!(code("purchase record --product Lamp --price 10"), 2);
+        assert_eq!(code("purchase record --product Lamp --quantity 2"), 2);
+        assert_eq!(code("sale record --product Lamp"), 2);
+        assert_eq!(code("price schedule --product Lamp --from 2030-01-01"), 2);
+        assert!(!session.run("product list --format csv").unwrap().contains("Mug"));
+    }
+
+    #[test]
+    fn test_interactive_data_path() {
+        let args = |line: &str| line.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        let env = |name: &str| (name == "INVENTORY_DATA").then(|| "shop.json".to_string());
+
+        assert_eq!(interactive_data_path(&args(""), &env), Some(PathBuf::from("shop.json")));
+        assert_eq!(interactive_data_path(&args("--data other.json"), &env), Some(PathBuf::from("other.json")));
+        assert_eq!(interactive_data_path(&args(""), &|_| None), Some(PathBuf::from("inventory.json")));
+        assert_eq!(interactive_data_path(&args("--data other.json product list"), &env), None);
+        assert_eq!(interactive_data_path(&args("--help"), &env), None);
+    }
+
+    #[test]
+    fn test_credentials() {
+        let path = std::env::temp_dir().join(format!("inventory-cli-credentials-{}", std::process::id()));
+        fs::write(&path, "# cron job\nusername = manager\npassword=wrong\n").unwrap();
+        let args = vec![
+            "--credentials".to_string(),
+            path.display().to_string(),
+            "product".to_string(),
+            "list".to_string(),
+        ];
+
+        let result = run(&args, &Auth::new(), &|_| None, &mut Vec::new());
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(CliError::Auth(_))));
+        assert_eq!(result.unwrap_err().exit_code(), 3);
+        let result = run(&args[2..], &Auth::new(), &|_| None, &mut Vec::new());
+        assert_eq!(result.unwrap_err().exit_code(), 2);
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use std::fmt;
+
+/// Source of the current time for everything the inventory records. The
+/// application uses `SystemClock`; tests swap in a `FixedClock` so dates are
+/// predictable.
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+pub struct FixedClock(pub NaiveDateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.0
+    }
+}
+
+/// An inclusive range of calendar days used to select transactions for totals
+/// and reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        DateRange { from, to }
+    }
+
+    /// A range covering every date, for lifetime totals.
+    pub fn all() -> Self {
+        DateRange {
+            from: NaiveDate::MIN,
+            to: NaiveDate::MAX,
+        }
+    }
+
+    pub fn contains(&self, timestamp: &NaiveDateTime) -> bool {
+        let date = timestamp.date();
+        self.from <= date && date <= self.to
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == DateRange::all() {
+            write!(f, "All dates")
+        } else {
+            write!(f, "{} to {}", self.from, self.to)
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::inventory::{Inventory, InventoryError};
+use chrono::{Duration, NaiveDateTime};
+use std::fmt;
+
+/// How future daily demand is projected from past daily demand.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ForecastMethod {
+    /// The mean of the last `n` days.
+    MovingAverage(usize),
+    /// A level updated each day by `alpha` times the latest error.
+    ExponentialSmoothing(f64),
+    /// Repeats the last full season of `n` days, e.g. 7 for a weekly pattern.
+    SeasonalNaive(usize),
+}
+
+impl ForecastMethod {
+    /// Days of history needed before the method can forecast at all.
+    fn required_history(&self) -> usize {
+        match self {
+            ForecastMethod::MovingAverage(window) => *window,
+            ForecastMethod::ExponentialSmoothing(_) => 1,
+            ForecastMethod::SeasonalNaive(season) => *season,
+        }
+    }
+
+    fn validate(&self) -> Result<(), InventoryError> {
+        match self {
+            ForecastMethod::MovingAverage(0) => {
+                Err(InventoryError::InvalidInput("moving average window must be at least 1 day".to_string()))
+            }
+            ForecastMethod::ExponentialSmoothing(alpha) if !(*alpha > 0.0 && *alpha <= 1.0) => Err(
+                InventoryError::InvalidInput(format!("smoothing factor must be above 0 and at most 1, got {}", alpha)),
+            ),
+            ForecastMethod::SeasonalNaive(0) => {
+                Err(InventoryError::InvalidInput("season length must be at least 1 day".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Daily forecasts for the `horizon` days that follow `history`.
+    fn project(&self, history: &[f64], horizon: usize) -> Vec<f64> {
+        match self {
+            ForecastMethod::MovingAverage(window) => {
+                let recent = &history[history.len() - window..];
+                vec![recent.iter().sum::<f64>() / *window as f64; horizon]
+            }
+            ForecastMethod::ExponentialSmoothing(alpha) => {
+                let level = history[1..]
+                    .iter()
+                    .fold(history[0], |level, demand| level + alpha * (demand - level));
+                vec![level; horizon]
+            }
+            ForecastMethod::SeasonalNaive(season) => {
+                let last_season = &history[history.len() - season..];
+                (0..horizon).map(|day| last_season[day % season]).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for ForecastMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForecastMethod::MovingAverage(window) => write!(f, "{}-day moving average", window),
+            ForecastMethod::ExponentialSmoothing(alpha) => write!(f, "Exponential smoothing (alpha {})", alpha),
+            ForecastMethod::SeasonalNaive(season) => write!(f, "Seasonal naive ({}-day season)", season),
+        }
+    }
+}
+
+/// Longest sales history a forecast will look back over, about ten years.
+pub const MAX_HISTORY_DAYS: usize = 3650;
+
+pub struct ForecastSettings {
+    pub method: ForecastMethod,
+    /// Days of sales history to learn from, ending yesterday.
+    pub history_days: usize,
+    /// Days of demand the suggested reorder should cover, including the supplier lead time.
+    pub horizon_days: usize,
+}
+
+impl Default for ForecastSettings {
+    fn default() -> Self {
+        ForecastSettings {
+            method: ForecastMethod::MovingAverage(7),
+            history_days: 56,
+            horizon_days: 14,
+        }
+    }
+}
+
+impl ForecastSettings {
+    pub fn validate(&self) -> Result<(), InventoryError> {
+        self.method.validate()?;
+        let required = self.method.required_history();
+        if self.history_days <= required {
+            return Err(InventoryError::InvalidInput(format!(
+                "{} needs more than {} days of history",
+                self.method, required
+            )));
+        }
+        if self.history_days > MAX_HISTORY_DAYS {
+            return Err(InventoryError::InvalidInput(format!(
+                "history is limited to {} days, got {}",
+                MAX_HISTORY_DAYS, self.history_days
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub struct ProductForecast {
+    pub on_hand: u32,
+    /// Forecast units for each day of the horizon, starting today.
+    pub daily_demand: Vec<f64>,
+    /// Mean absolute error of one-day-ahead forecasts replayed over the history.
+    pub mean_absolute_error: f64,
+}
+
+impl ProductForecast {
+    pub fn total_demand(&self) -> f64 {
+        self.daily_demand.iter().sum()
+    }
+
+    /// Units to order so stock on hand covers the forecast demand over the horizon.
+    pub fn suggested_order(&self) -> u32 {
+        (self.total_demand().ceil() - self.on_hand as f64).max(0.0) as u32
+    }
+}
+
+#[allow(dead_code)]
+pub trait Forecasting {
+    fn demand_history(&self, product_name: &str, days: usize) -> Vec<f64>;
+    fn forecast_demand(&self, product_name: &str, settings: &ForecastSettings) -> Result<ProductForecast, InventoryError>;
+}
+
+impl Forecasting for Inventory {
+    /// Units sold per day, net of customer returns, for the `days` days ending
+    /// yesterday, oldest first.
+    fn demand_history(&self, product_name: &str, days: usize) -> Vec<f64> {
+        let first_day = self.clock.now().date() - Duration::days(days as i64);
+        let mut history = vec![0.0; days];
+        let mut record = |timestamp: NaiveDateTime, units: f64| {
+            let offset = (timestamp.date() - first_day).num_days();
+            if (0..days as i64).contains(&offset) {
+                history[offset as usize] += units;
+            }
+        };
+        for sale in self.sales.iter().filter(|s| s.product_name == product_name) {
+            record(sale.timestamp, sale.quantity_sold as f64);
+        }
+        for sale_return in self.returns.iter().filter(|r| r.product_name == product_name) {
+            record(sale_return.timestamp, -(sale_return.quantity_returned() as f64));
+        }
+        history.into_iter().map(|units| units.max(0.0)).collect()
+    }
+
+    fn forecast_demand(&self, product_name: &str, settings: &ForecastSettings) -> Result<ProductForecast, InventoryError> {
+        settings.validate()?;
+        let required = settings.method.required_history();
+        let product = match self.products.iter().find(|p| p.name == product_name) {
+            Some(product) => product,
+            None => return Err(InventoryError::ProductNotFound(product_name.to_string())),
+        };
+
+        let history = self.demand_history(product_name, settings.history_days);
+        let errors: Vec<f64> = (required..history.len())
+            .map(|day| (settings.method.project(&history[..day], 1)[0] - history[day]).abs())
+            .collect();
+
+        Ok(ProductForecast {
+            on_hand: product.quantity,
+            daily_demand: settings.method.project(&history, settings.horizon_days),
+            mean_absolute_error: errors.iter().sum::<f64>() / errors.len() as f64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+    use chrono::NaiveDate;
+
+    // Four weeks of sales with a weekly pattern: 6 units on Saturdays, 1 on other days.
+    fn inventory_with_weekly_sales() -> Inventory {
+        let first_day = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(); // a Saturday
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(first_day.and_hms_opt(9, 0, 0).unwrap())));
+        inventory.add_product("Bread".to_string(), "Sourdough loaf".to_string(), 2.0, 500).unwrap();
+        for day in 0..28 {
+            let date = first_day + Duration::days(day);
+            inventory.clock = Box::new(FixedClock(date.and_hms_opt(9, 0, 0).unwrap()));
+            inventory.record_sale("Bread", if day % 7 == 0 { 6 } else { 1 }, 4.0).unwrap();
+        }
+        let today = first_day + Duration::days(28);
+        inventory.clock = Box::new(FixedClock(today.and_hms_opt(9, 0, 0).unwrap()));
+        inventory
+    }
+
+    #[test]
+    fn test_demand_history() {
+        let inventory = inventory_with_weekly_sales();
+
+        let history = inventory.demand_history("Bread", 7);
+        assert_eq!(history, vec![6.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_forecast_methods() {
+        let inventory = inventory_with_weekly_sales();
+        let mut settings = ForecastSettings {
+            method: ForecastMethod::MovingAverage(7),
+            history_days: 28,
+            horizon_days: 7,
+        };
+
+        let forecast = inventory.forecast_demand("Bread", &settings).unwrap();
+        assert!((forecast.total_demand() - 12.0).abs() < 1e-9);
+        assert_eq!(forecast.suggested_order(), 0);
+
+        settings.method = ForecastMethod::SeasonalNaive(7);
+        let forecast = inventory.forecast_demand("Bread", &settings).unwrap();
+        assert_eq!(forecast.daily_demand[0], 6.0);
+        assert_eq!(forecast.mean_absolute_error, 0.0);
+
+        settings.method = ForecastMethod::ExponentialSmoothing(0.5);
+        let forecast = inventory.forecast_demand("Bread", &settings).unwrap();
+        assert!(forecast.mean_absolute_error > 0.0);
+    }
+
+    #[test]
+    fn test_suggested_order_and_invalid_settings() {
+        let mut inventory = inventory_with_weekly_sales();
+        inventory.products[0].quantity = 5;
+        let settings = ForecastSettings {
+            method: ForecastMethod::SeasonalNaive(7),
+            history_days: 28,
+            horizon_days: 14,
+        };
+        assert_eq!(inventory.forecast_demand("Bread", &settings).unwrap().suggested_order(), 19);
+
+        let settings = ForecastSettings {
+            method: ForecastMethod::SeasonalNaive(7),
+            history_days: 7,
+            horizon_days: 14,
+        };
+        assert!(matches!(
+            inventory.forecast_demand("Bread", &settings),
+            Err(InventoryError::InvalidInput(_))
+        ));
+        let settings = ForecastSettings {
+            history_days: MAX_HISTORY_DAYS + 1,
+            ..ForecastSettings::default()
+        };
+        assert!(inventory.forecast_demand("Bread", &settings).is_err());
+        let settings = ForecastSettings {
+            method: ForecastMethod::ExponentialSmoothing(1.5),
+            ..ForecastSettings::default()
+        };
+        assert!(inventory.forecast_demand("Bread", &settings).is_err());
+    }
+}
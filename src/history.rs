@@ -0,0 +1,484 @@
+use crate::adjustments::StockAdjustment;
+use crate::inventory::{Inventory, InventoryError, Product};
+use crate::pricing::{PriceChange, PriceManagement};
+use crate::purchases::Purchase;
+use crate::sales::Sale;
+use chrono::NaiveDateTime;
+
+/// A change made during the session, holding what is needed to reverse it
+/// and to make it again.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Price changes scheduled after adding are kept here once the add is undone.
+    AddProduct { product: Product, price_changes: Vec<PriceChange> },
+    EditProduct { before: Product, after: Product },
+    /// Deleting a product also drops its price history, kept here to restore.
+    DeleteProduct { index: usize, product: Product, price_changes: Vec<PriceChange> },
+    /// One sale, or every line of a checkout receipt.
+    Sale(Vec<Sale>),
+    Purchase(Purchase),
+    Adjustment(StockAdjustment),
+}
+
+fn refused(reason: String) -> InventoryError {
+    InventoryError::UndoFailed(reason)
+}
+
+fn product_mut<'a>(inventory: &'a mut Inventory, name: &str) -> Result<&'a mut Product, InventoryError> {
+    inventory
+        .products
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| refused(format!("product '{}' no longer exists", name)))
+}
+
+fn ensure_name_free(inventory: &Inventory, name: &str) -> Result<(), InventoryError> {
+    if inventory.products.iter().any(|p| p.name == name) {
+        return Err(refused(format!("another product is now called '{}'", name)));
+    }
+    Ok(())
+}
+
+// A product must be exactly as the operation left it, otherwise a later
+// change depends on it.
+fn ensure_unchanged(inventory: &Inventory, expected: &Product) -> Result<(), InventoryError> {
+    match inventory.products.iter().find(|p| p.name == expected.name) {
+        Some(product) if product == expected => Ok(()),
+        Some(_) => Err(refused(format!("'{}' has changed since", expected.name))),
+        None => Err(refused(format!("product '{}' no longer exists", expected.name))),
+    }
+}
+
+// Stock adjusted or counted after an operation was measured with it in place,
+// so reversing it would contradict the books. Times that cannot be told apart
+// count as later.
+fn ensure_not_counted_since(inventory: &Inventory, product_name: &str, since: NaiveDateTime) -> Result<(), InventoryError> {
+    if inventory.adjustments.iter().any(|a| a.product_name == product_name && a.timestamp >= since) {
+        return Err(refused(format!("stock of '{}' has been adjusted since", product_name)));
+    }
+    let counted = inventory
+        .stock_takes
+        .iter()
+        .find(|t| t.posted && t.started_at >= since && t.lines.iter().any(|l| l.product_name == product_name));
+    match counted {
+        Some(stock_take) => Err(refused(format!("'{}' was counted in stock take #{} since", product_name, stock_take.id))),
+        None => Ok(()),
+    }
+}
+
+fn describe_sales(sales: &[Sale]) -> String {
+    sales
+        .iter()
+        .map(|s| format!("sale #{} of {} x {} at ${:.2}", s.id, s.quantity_sold, s.product_name, s.sale_price))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// The single place where a product is swapped for another version of itself,
+// carrying a rename through its records. A different price goes into the
+// price history like any other change.
+fn replace_product(
+    inventory: &mut Inventory,
+    current: &Product,
+    replacement: &Product,
+    user: &str,
+) -> Result<(), InventoryError> {
+    ensure_unchanged(inventory, current)?;
+    if replacement.name != current.name {
+        ensure_name_free(inventory, &replacement.name)?;
+        inventory.rename_product(&current.name, &replacement.name);
+    }
+    *product_mut(inventory, &replacement.name)? = Product {
+        price: current.price,
+        ..replacement.clone()
+    };
+    if replacement.price != current.price {
+        inventory.change_price(&replacement.name, replacement.price, user)?;
+    }
+    Ok(())
+}
+
+impl Operation {
+    /// What undoing the operation will change, for the confirmation prompt.
+    pub fn undo_summary(&self) -> String {
+        match self {
+            Operation::AddProduct { product, .. } => format!("Remove the newly added product '{}'.", product.name),
+            Operation::EditProduct { before, after } => format!(
+                "Restore '{}' to: name '{}', description '{}', price ${:.2}, quantity {}, category '{}', SKU '{}', reorder level {}{}.",
+                after.name,
+                before.name,
+                before.description,
+                before.price,
+                before.quantity,
+                before.category,
+                before.sku,
+                before.reorder_level,
+                if before.archived { ", archived" } else { "" }
+            ),
+            Operation::DeleteProduct { product, .. } => format!(
+                "Restore the deleted product '{}' with {} in stock at ${:.2}.",
+                product.name, product.quantity, product.price
+            ),
+            Operation::Sale(sales) => format!(
+                "Cancel {} and put the units back in stock.",
+                describe_sales(sales)
+            ),
+            Operation::Purchase(purchase) => format!(
+                "Cancel purchase #{} of {} x {} at ${:.2} and take the units out of stock.",
+                purchase.id, purchase.quantity_purchased, purchase.product_name, purchase.purchase_price
+            ),
+            Operation::Adjustment(adjustment) => format!(
+                "Cancel the {} adjustment of {:+} to {}.",
+                adjustment.reason, adjustment.quantity_change, adjustment.product_name
+            ),
+        }
+    }
+
+    /// What redoing the operation will change.
+    pub fn redo_summary(&self) -> String {
+        match self {
+            Operation::AddProduct { product, .. } => format!("Add the product '{}' again.", product.name),
+            Operation::EditProduct { before, after } => format!(
+                "Apply the edit of '{}' again: name '{}', price ${:.2}, quantity {}.",
+                before.name, after.name, after.price, after.quantity
+            ),
+            Operation::DeleteProduct { product, .. } => format!("Delete the product '{}' again.", product.name),
+            Operation::Sale(sales) => format!("Record {} again.", describe_sales(sales)),
+            Operation::Purchase(purchase) => format!(
+                "Record purchase #{} of {} x {} again.",
+                purchase.id, purchase.quantity_purchased, purchase.product_name
+            ),
+            Operation::Adjustment(adjustment) => format!(
+                "Apply the {} adjustment of {:+} to {} again.",
+                adjustment.reason, adjustment.quantity_change, adjustment.product_name
+            ),
+        }
+    }
+
+    // Each check comes before any change, so a refused undo changes nothing.
+    fn undo(&mut self, inventory: &mut Inventory, user: &str) -> Result<(), InventoryError> {
+        match self {
+            Operation::AddProduct { product, price_changes } => {
+                ensure_unchanged(inventory, product)?;
+                if inventory.has_transactions(&product.name) {
+                    return Err(refused(format!("'{}' has transactions recorded since it was added", product.name)));
+                }
+                inventory.products.retain(|p| p.name != product.name);
+                let (removed, kept) = inventory.price_changes.drain(..).partition(|c| c.product_name == product.name);
+                *price_changes = removed;
+                inventory.price_changes = kept;
+            }
+            Operation::EditProduct { before, after } => replace_product(inventory, after, before, user)?,
+            Operation::DeleteProduct { index, product, price_changes } => {
+                ensure_name_free(inventory, &product.name)?;
+                let index = (*index).min(inventory.products.len());
+                inventory.products.insert(index, product.clone());
+                inventory.price_changes.extend(price_changes.iter().cloned());
+            }
+            Operation::Sale(sales) => {
+                let first = inventory.sales.len().checked_sub(sales.len());
+                if first.map(|first| &inventory.sales[first..]) != Some(sales.as_slice()) {
+                    return Err(refused("other sales have been recorded since".to_string()));
+                }
+                if let Some(r) = inventory.returns.iter().find(|r| sales.iter().any(|s| s.id == r.sale_id)) {
+                    return Err(refused(format!("sale #{} has a customer return", r.sale_id)));
+                }
+                for sale in sales.iter() {
+                    product_mut(inventory, &sale.product_name)?;
+                    ensure_not_counted_since(inventory, &sale.product_name, sale.timestamp)?;
+                }
+                for sale in sales.iter() {
+                    product_mut(inventory, &sale.product_name)?.quantity += sale.quantity_sold;
+                }
+                inventory.sales.truncate(inventory.sales.len() - sales.len());
+            }
+            Operation::Purchase(purchase) => {
+                if inventory.purchases.last() != Some(purchase) {
+                    return Err(refused("other purchases have been recorded since".to_string()));
+                }
+                if inventory.supplier_returns.iter().any(|r| r.purchase_id == purchase.id) {
+                    return Err(refused(format!("purchase #{} has goods returned to the supplier", purchase.id)));
+                }
+                ensure_not_counted_since(inventory, &purchase.product_name, purchase.timestamp)?;
+                let product = product_mut(inventory, &purchase.product_name)?;
+                if product.quantity < purchase.quantity_purchased {
+                    return Err(refused(format!("some of the {} purchased have been sold or used since", purchase.product_name)));
+                }
+                product.quantity -= purchase.quantity_purchased;
+                inventory.purchases.pop();
+            }
+            Operation::Adjustment(adjustment) => {
+                if inventory.adjustments.last() != Some(adjustment) {
+                    return Err(refused("other stock adjustments have been recorded since".to_string()));
+                }
+                let product = product_mut(inventory, &adjustment.product_name)?;
+                let quantity = product.quantity as i64 - adjustment.quantity_change;
+                product.quantity = match u32::try_from(quantity) {
+                    Ok(quantity) => quantity,
+                    Err(_) if quantity < 0 => {
+                        return Err(refused(format!("not enough {} left in stock", adjustment.product_name)))
+                    }
+                    Err(_) => return Err(refused(format!("the stock of {} would be too large", adjustment.product_name))),
+                };
+                inventory.adjustments.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn redo(&self, inventory: &mut Inventory, user: &str) -> Result<(), InventoryError> {
+        match self {
+            Operation::AddProduct { product, price_changes } => {
+                ensure_name_free(inventory, &product.name)?;
+                inventory.products.push(product.clone());
+                inventory.price_changes.extend(price_changes.iter().cloned());
+            }
+            Operation::EditProduct { before, after } => replace_product(inventory, before, after, user)?,
+            Operation::DeleteProduct { product, .. } => {
+                ensure_unchanged(inventory, product)?;
+                inventory.products.retain(|p| p.name != product.name);
+                inventory.price_changes.retain(|c| c.product_name != product.name);
+            }
+            Operation::Sale(sales) => {
+                if inventory.sales.len() as u32 + 1 != sales[0].id {
+                    return Err(refused("other sales have been recorded since".to_string()));
+                }
+                for sale in sales {
+                    if product_mut(inventory, &sale.product_name)?.quantity < sale.quantity_sold {
+                        return Err(InventoryError::OutOfStock(sale.product_name.clone()));
+                    }
+                }
+                for sale in sales {
+                    product_mut(inventory, &sale.product_name)?.quantity -= sale.quantity_sold;
+                }
+                inventory.sales.extend(sales.iter().cloned());
+            }
+            Operation::Purchase(purchase) => {
+                if inventory.purchases.len() as u32 + 1 != purchase.id {
+                    return Err(refused("other purchases have been recorded since".to_string()));
+                }
+                product_mut(inventory, &purchase.product_name)?.quantity += purchase.quantity_purchased;
+                inventory.purchases.push(purchase.clone());
+            }
+            Operation::Adjustment(adjustment) => {
+                let product = product_mut(inventory, &adjustment.product_name)?;
+                let quantity = product.quantity as i64 + adjustment.quantity_change;
+                product.quantity = match u32::try_from(quantity) {
+                    Ok(quantity) => quantity,
+                    Err(_) if quantity < 0 => return Err(InventoryError::OutOfStock(adjustment.product_name.clone())),
+                    Err(_) => return Err(refused(format!("the stock of {} would be too large", adjustment.product_name))),
+                };
+                inventory.adjustments.push(adjustment.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Undo and redo stacks for the current session. Only the most recent
+/// operation can be undone, and only while nothing done since depends on it.
+#[derive(Default)]
+pub struct History {
+    done: Vec<Operation>,
+    undone: Vec<Operation>,
+}
+
+impl History {
+    /// Remembers a completed operation. Anything undone can no longer be redone.
+    pub fn record(&mut self, operation: Operation) {
+        self.done.push(operation);
+        self.undone.clear();
+    }
+
+    pub fn next_undo(&self) -> Option<&Operation> {
+        self.done.last()
+    }
+
+    pub fn next_redo(&self) -> Option<&Operation> {
+        self.undone.last()
+    }
+
+    /// Reverses the most recent operation on behalf of `user`. When it is
+    /// refused, the operation stays on the stack and the inventory is unchanged.
+    pub fn undo(&mut self, inventory: &mut Inventory, user: &str) -> Result<(), InventoryError> {
+        let operation = self.done.last_mut().ok_or_else(|| refused("nothing to undo".to_string()))?;
+        operation.undo(inventory, user)?;
+        self.undone.extend(self.done.pop());
+        Ok(())
+    }
+
+    pub fn redo(&mut self, inventory: &mut Inventory, user: &str) -> Result<(), InventoryError> {
+        let operation = self.undone.last().ok_or_else(|| refused("nothing to redo".to_string()))?;
+        operation.redo(inventory, user)?;
+        self.done.extend(self.undone.pop());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjustments::{AdjustmentManagement, AdjustmentReason};
+    use crate::inventory::InventoryManagement;
+    use crate::purchases::PurchaseManagement;
+    use crate::returns::ReturnManagement;
+    use crate::sales::SalesManagement;
+    use crate::stock_take::StockTakeManagement;
+
+    fn inventory_with_lamp() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Lamp".to_string(), "Desk lamp".to_string(), 20.0, 5).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_undo_and_redo_delete_and_edit() {
+        let mut inventory = inventory_with_lamp();
+        let mut history = History::default();
+
+        let product = inventory.products[0].clone();
+        inventory.delete_product("Lamp").unwrap();
+        history.record(Operation::DeleteProduct { index: 0, product, price_changes: Vec::new() });
+        history.undo(&mut inventory, "manager").unwrap();
+        assert_eq!(inventory.products[0].name, "Lamp");
+        history.redo(&mut inventory, "manager").unwrap();
+        assert!(inventory.products.is_empty());
+        history.undo(&mut inventory, "manager").unwrap();
+
+        let before = inventory.products[0].clone();
+        inventory.edit_product("Lamp", Some("Desk Lamp".to_string()), None, Some(25.0), None, "manager").unwrap();
+        history.record(Operation::EditProduct { before, after: inventory.products[0].clone() });
+        assert!(history.next_redo().is_none());
+        history.undo(&mut inventory, "manager").unwrap();
+        assert_eq!((inventory.products[0].name.as_str(), inventory.products[0].price), ("Lamp", 20.0));
+        assert_eq!(inventory.price_changes.len(), 2); // the undo is a price change too
+    }
+
+    #[test]
+    fn test_undo_delete_restores_scheduled_prices() {
+        let mut inventory = inventory_with_lamp();
+        let mut history = History::default();
+        let next_week = inventory.clock.now() + chrono::Duration::days(7);
+        inventory.schedule_price("Lamp", 24.0, next_week, None, "manager").unwrap();
+
+        let product = inventory.products[0].clone();
+        let price_changes = inventory.price_changes.clone();
+        inventory.delete_product("Lamp").unwrap();
+        history.record(Operation::DeleteProduct { index: 0, product, price_changes });
+        assert!(inventory.price_changes.is_empty());
+
+        history.undo(&mut inventory, "manager").unwrap();
+        assert_eq!(inventory.price_at("Lamp", next_week), Some(24.0));
+        history.redo(&mut inventory, "manager").unwrap();
+        assert!(inventory.price_changes.is_empty());
+    }
+
+    #[test]
+    fn test_undo_add_removes_scheduled_prices() {
+        let mut inventory = Inventory::new();
+        let mut history = History::default();
+        inventory.add_product("Lamp".to_string(), String::new(), 20.0, 5).unwrap();
+        history.record(Operation::AddProduct { product: inventory.products[0].clone(), price_changes: Vec::new() });
+        let next_week = inventory.clock.now() + chrono::Duration::days(7);
+        inventory.schedule_price("Lamp", 24.0, next_week, None, "manager").unwrap();
+
+        history.undo(&mut inventory, "manager").unwrap();
+        assert!(inventory.price_changes.is_empty());
+        history.redo(&mut inventory, "manager").unwrap();
+        assert_eq!(inventory.price_at("Lamp", next_week), Some(24.0));
+
+        history.undo(&mut inventory, "manager").unwrap();
+        inventory.add_product("Lamp".to_string(), String::new(), 30.0, 5).unwrap();
+        assert_eq!(inventory.price_at("Lamp", next_week), Some(30.0));
+    }
+
+    #[test]
+    fn test_undo_sale_and_dependent_return() {
+        let mut inventory = inventory_with_lamp();
+        let mut history = History::default();
+
+        inventory.record_sale("Lamp", 2, 25.0).unwrap();
+        history.record(Operation::Sale(vec![inventory.sales[0].clone()]));
+        assert_eq!(
+            history.next_undo().unwrap().undo_summary(),
+            "Cancel sale #1 of 2 x Lamp at $25.00 and put the units back in stock."
+        );
+
+        inventory.record_return(1, 1, 0).unwrap();
+        assert_eq!(
+            history.undo(&mut inventory, "manager"),
+            Err(InventoryError::UndoFailed("sale #1 has a customer return".to_string()))
+        );
+        assert_eq!(inventory.sales.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_refused_after_adjustment_or_count() {
+        let mut inventory = inventory_with_lamp();
+        let mut history = History::default();
+        inventory.record_sale("Lamp", 1, 25.0).unwrap();
+        history.record(Operation::Sale(vec![inventory.sales[0].clone()]));
+        inventory.adjust_stock("Lamp", -1, AdjustmentReason::Damage, "manager").unwrap();
+
+        assert_eq!(
+            history.undo(&mut inventory, "manager"),
+            Err(InventoryError::UndoFailed("stock of 'Lamp' has been adjusted since".to_string()))
+        );
+
+        let mut history = History::default();
+        inventory.record_purchase("Lamp", 4, 12.0).unwrap();
+        history.record(Operation::Purchase(inventory.purchases[0].clone()));
+        let stock_take = inventory.start_stock_take(&["Lamp".to_string()]).unwrap();
+        inventory.record_count(stock_take, "Lamp", 7).unwrap();
+        inventory.post_stock_take(stock_take, "manager").unwrap();
+
+        assert_eq!(
+            history.undo(&mut inventory, "manager"),
+            Err(InventoryError::UndoFailed(format!("'Lamp' was counted in stock take #{} since", stock_take)))
+        );
+        assert_eq!(inventory.purchases.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_and_redo_adjustment_refused_past_largest_quantity() {
+        let mut inventory = inventory_with_lamp();
+        let mut history = History::default();
+        inventory.adjust_stock("Lamp", -3, AdjustmentReason::Damage, "manager").unwrap();
+        history.record(Operation::Adjustment(inventory.adjustments[0].clone()));
+        inventory.products[0].quantity = u32::MAX - 1;
+
+        assert_eq!(
+            history.undo(&mut inventory, "manager"),
+            Err(InventoryError::UndoFailed("the stock of Lamp would be too large".to_string()))
+        );
+        assert_eq!(inventory.products[0].quantity, u32::MAX - 1);
+
+        inventory.products[0].quantity = 2;
+        history.undo(&mut inventory, "manager").unwrap();
+        assert_eq!(inventory.products[0].quantity, 5);
+
+        let mut history = History::default();
+        inventory.adjust_stock("Lamp", 3, AdjustmentReason::Found, "manager").unwrap();
+        history.record(Operation::Adjustment(inventory.adjustments[0].clone()));
+        history.undo(&mut inventory, "manager").unwrap();
+        inventory.products[0].quantity = u32::MAX - 1;
+
+        assert_eq!(
+            history.redo(&mut inventory, "manager"),
+            Err(InventoryError::UndoFailed("the stock of Lamp would be too large".to_string()))
+        );
+        assert!(inventory.adjustments.is_empty());
+    }
+
+    #[test]
+    fn test_undo_add_refused_after_transactions() {
+        let mut inventory = Inventory::new();
+        let mut history = History::default();
+        inventory.add_product("Lamp".to_string(), String::new(), 20.0, 5).unwrap();
+        history.record(Operation::AddProduct { product: inventory.products[0].clone(), price_changes: Vec::new() });
+
+        inventory.record_sale("Lamp", 1, 25.0).unwrap();
+        assert!(matches!(history.undo(&mut inventory, "manager"), Err(InventoryError::UndoFailed(_))));
+        assert!(history.redo(&mut inventory, "manager").is_err());
+    }
+}
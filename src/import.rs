@@ -0,0 +1,729 @@
+use crate::adjustments::{AdjustmentManagement, AdjustmentReason};
+use crate::inventory::{Inventory, InventoryError, Product};
+use crate::pricing::PriceManagement;
+use crate::purchases::Purchase;
+use crate::sales::Sale;
+use crate::valuation::Valuation;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+/// Product fields the importer understands, in the order they are resolved.
+pub const PRODUCT_FIELDS: [&str; 7] = ["name", "description", "price", "quantity", "category", "sku", "reorder_level"];
+const REQUIRED_PRODUCT_FIELDS: [&str; 3] = ["name", "price", "quantity"];
+
+/// Fields of a historical sale or purchase. `supplier` only applies to purchases.
+pub const TRANSACTION_FIELDS: [&str; 5] = ["date", "product", "quantity", "price", "supplier"];
+const REQUIRED_TRANSACTION_FIELDS: [&str; 4] = ["date", "product", "quantity", "price"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Json,
+}
+
+impl FileFormat {
+    /// Picks the format from a file name, treating anything but `.json` as CSV.
+    pub fn from_path(path: &str) -> FileFormat {
+        if path.to_lowercase().ends_with(".json") {
+            FileFormat::Json
+        } else {
+            FileFormat::Csv
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ImportOptions {
+    /// Validate every row and report problems without changing the inventory.
+    pub dry_run: bool,
+    /// Product imports: update products that already exist instead of
+    /// reporting them as duplicates.
+    pub upsert: bool,
+    /// Sale and purchase imports: apply each transaction to current stock.
+    /// Leave off when loading history that today's quantities already reflect.
+    pub update_stock: bool,
+    /// Maps a field name (see `PRODUCT_FIELDS` and `TRANSACTION_FIELDS`) to the
+    /// CSV header or JSON key holding it, for files that use other names.
+    pub column_map: HashMap<String, String>,
+    /// Who is importing, recorded on the stock adjustments and price changes
+    /// an upsert makes.
+    pub user: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub error: InventoryError,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+struct ProductRow {
+    name: String,
+    description: Option<String>,
+    price: f64,
+    quantity: u32,
+    category: Option<String>,
+    sku: Option<String>,
+    reorder_level: Option<u32>,
+}
+
+#[allow(dead_code)]
+pub trait ImportManagement {
+    fn import_products<R: Read>(&mut self, reader: R, options: &ImportOptions) -> Result<ImportSummary, InventoryError>;
+    fn import_sales<R: Read>(
+        &mut self,
+        reader: R,
+        format: FileFormat,
+        options: &ImportOptions,
+    ) -> Result<ImportSummary, InventoryError>;
+    fn import_purchases<R: Read>(
+        &mut self,
+        reader: R,
+        format: FileFormat,
+        options: &ImportOptions,
+    ) -> Result<ImportSummary, InventoryError>;
+}
+
+/// One transaction as read from the file: field name to raw text, with the
+/// CSV line or JSON element number it came from.
+struct RawRow {
+    line: u64,
+    fields: HashMap<String, String>,
+}
+
+impl RawRow {
+    fn get(&self, field: &str) -> &str {
+        self.fields.get(field).map(|v| v.trim()).unwrap_or("")
+    }
+}
+
+fn read_csv_rows<R: Read>(reader: R, options: &ImportOptions) -> Result<Vec<RawRow>, InventoryError> {
+    let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv_reader.headers().map_err(csv_error)?.clone();
+    let columns = resolve_columns(&headers, &TRANSACTION_FIELDS, &REQUIRED_TRANSACTION_FIELDS, &options.column_map)?;
+
+    let mut rows = Vec::new();
+    for record in csv_reader.records() {
+        let record = record.map_err(csv_error)?;
+        let fields = columns
+            .iter()
+            .filter_map(|(name, &i)| record.get(i).map(|v| (name.clone(), v.to_string())))
+            .collect();
+        rows.push(RawRow {
+            line: record.position().map_or(0, |p| p.line()),
+            fields,
+        });
+    }
+    Ok(rows)
+}
+
+fn read_json_rows<R: Read>(reader: R, options: &ImportOptions) -> Result<Vec<RawRow>, InventoryError> {
+    let document: serde_json::Value = serde_json::from_reader(reader)
+        .map_err(|e| InventoryError::InvalidInput(format!("Could not read JSON: {}", e)))?;
+    let elements = match document.as_array() {
+        Some(elements) => elements,
+        None => return Err(InventoryError::InvalidInput("JSON file must contain a list of transactions".to_string())),
+    };
+
+    let mut rows = Vec::new();
+    for (i, element) in elements.iter().enumerate() {
+        let mut fields = HashMap::new();
+        if let Some(object) = element.as_object() {
+            for field in TRANSACTION_FIELDS {
+                let key = options.column_map.get(field).map(|k| k.as_str()).unwrap_or(field);
+                let value = object.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v);
+                match value {
+                    Some(serde_json::Value::String(text)) => {
+                        fields.insert(field.to_string(), text.clone());
+                    }
+                    Some(serde_json::Value::Null) | None => {}
+                    Some(other) => {
+                        fields.insert(field.to_string(), other.to_string());
+                    }
+                }
+            }
+        }
+        rows.push(RawRow {
+            line: i as u64 + 1,
+            fields,
+        });
+    }
+    Ok(rows)
+}
+
+fn read_rows<R: Read>(reader: R, format: FileFormat, options: &ImportOptions) -> Result<Vec<RawRow>, InventoryError> {
+    match format {
+        FileFormat::Csv => read_csv_rows(reader, options),
+        FileFormat::Json => read_json_rows(reader, options),
+    }
+}
+
+/// Accepts a plain date (taken as midnight) or a date and time.
+pub(crate) fn parse_timestamp(text: &str) -> Result<NaiveDateTime, InventoryError> {
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(timestamp);
+        }
+    }
+    match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        Ok(date) => Ok(date.and_hms_opt(0, 0, 0).unwrap()),
+        Err(_) => Err(InventoryError::InvalidInput(format!("'{}' is not a valid date", text))),
+    }
+}
+
+struct TransactionRow {
+    timestamp: NaiveDateTime,
+    product_name: String,
+    quantity: u32,
+    price: f64,
+    supplier: String,
+}
+
+fn parse_transaction_row(inventory: &Inventory, row: &RawRow) -> Result<TransactionRow, InventoryError> {
+    let timestamp = parse_timestamp(row.get("date"))?;
+    let quantity = parse_quantity(row.get("quantity"))?;
+    let price = parse_price(row.get("price"))?;
+    let product_name = row.get("product");
+    if !inventory.products.iter().any(|p| p.name == product_name) {
+        return Err(InventoryError::ProductNotFound(product_name.to_string()));
+    }
+    let supplier = match row.get("supplier") {
+        "" => "Unknown supplier",
+        supplier => supplier,
+    };
+
+    Ok(TransactionRow {
+        timestamp,
+        product_name: product_name.to_string(),
+        quantity,
+        price,
+        supplier: supplier.to_string(),
+    })
+}
+
+fn csv_error(error: csv::Error) -> InventoryError {
+    InventoryError::InvalidInput(format!("Could not read CSV: {}", error))
+}
+
+/// Finds the column index of each field, matching headers case-insensitively.
+fn resolve_columns(
+    headers: &csv::StringRecord,
+    fields: &[&str],
+    required: &[&str],
+    column_map: &HashMap<String, String>,
+) -> Result<HashMap<String, usize>, InventoryError> {
+    let mut columns = HashMap::new();
+    for field in fields {
+        let header = column_map.get(*field).map(|h| h.as_str()).unwrap_or(field);
+        match headers.iter().position(|h| h.trim().eq_ignore_ascii_case(header.trim())) {
+            Some(index) => {
+                columns.insert(field.to_string(), index);
+            }
+            None if required.contains(field) => {
+                return Err(InventoryError::InvalidInput(format!("Missing column '{}'", header)))
+            }
+            None => {}
+        }
+    }
+    Ok(columns)
+}
+
+fn field<'r>(record: &'r csv::StringRecord, columns: &HashMap<String, usize>, name: &str) -> Option<&'r str> {
+    columns.get(name).and_then(|&i| record.get(i)).map(|v| v.trim())
+}
+
+pub(crate) fn parse_price(text: &str) -> Result<f64, InventoryError> {
+    match text.trim_start_matches('$').parse::<f64>() {
+        Ok(price) if price.is_finite() && price > 0.0 => Ok(price),
+        Ok(price) if price.is_finite() => Err(InventoryError::InvalidPrice(price)),
+        _ => Err(InventoryError::InvalidInput(format!("'{}' is not a valid price", text))),
+    }
+}
+
+pub(crate) fn parse_quantity(text: &str) -> Result<u32, InventoryError> {
+    match text.parse::<u32>() {
+        Ok(0) => Err(InventoryError::InvalidQuantity(0)),
+        Ok(quantity) => Ok(quantity),
+        Err(_) => Err(InventoryError::InvalidInput(format!("'{}' is not a valid quantity", text))),
+    }
+}
+
+fn parse_product_row(record: &csv::StringRecord, columns: &HashMap<String, usize>) -> Result<ProductRow, InventoryError> {
+    let name = field(record, columns, "name").unwrap_or("");
+    if name.is_empty() {
+        return Err(InventoryError::InvalidInput("Product name cannot be empty".to_string()));
+    }
+    let optional = |column| field(record, columns, column).filter(|v| !v.is_empty()).map(|v| v.to_string());
+    let reorder_level = match optional("reorder_level") {
+        Some(text) => Some(
+            text.parse::<u32>()
+                .map_err(|_| InventoryError::InvalidInput(format!("'{}' is not a valid reorder level", text)))?,
+        ),
+        None => None,
+    };
+
+    Ok(ProductRow {
+        name: name.to_string(),
+        description: optional("description"),
+        price: parse_price(field(record, columns, "price").unwrap_or(""))?,
+        quantity: parse_quantity(field(record, columns, "quantity").unwrap_or(""))?,
+        category: optional("category"),
+        sku: optional("sku"),
+        reorder_level,
+    })
+}
+
+impl ImportManagement for Inventory {
+    fn import_products<R: Read>(&mut self, reader: R, options: &ImportOptions) -> Result<ImportSummary, InventoryError> {
+        let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = csv_reader.headers().map_err(csv_error)?.clone();
+        let columns = resolve_columns(&headers, &PRODUCT_FIELDS, &REQUIRED_PRODUCT_FIELDS, &options.column_map)?;
+
+        let mut summary = ImportSummary::default();
+        let mut seen = HashSet::new();
+        let mut seen_skus: HashMap<String, String> = HashMap::new();
+        // Rows are only applied once the whole file has been read, so an
+        // unreadable record cannot leave a partial import behind.
+        let mut accepted = Vec::new();
+        for record in csv_reader.records() {
+            let record = record.map_err(csv_error)?;
+            let line = record.position().map_or(0, |p| p.line());
+
+            let row = match parse_product_row(&record, &columns) {
+                Ok(row) => row,
+                Err(error) => {
+                    summary.errors.push(ImportRowError { line, error });
+                    continue;
+                }
+            };
+
+            let exists = seen.contains(&row.name) || self.products.iter().any(|p| p.name == row.name);
+            if exists && !options.upsert {
+                summary.errors.push(ImportRowError {
+                    line,
+                    error: InventoryError::DuplicateProduct(row.name),
+                });
+                continue;
+            }
+            if let Some(sku) = &row.sku {
+                let owner = seen_skus
+                    .get(sku)
+                    .or_else(|| self.products.iter().find(|p| &p.sku == sku).map(|p| &p.name));
+                if let Some(owner) = owner.filter(|owner| **owner != row.name) {
+                    summary.errors.push(ImportRowError {
+                        line,
+                        error: InventoryError::SkuInUse(sku.clone(), owner.clone()),
+                    });
+                    continue;
+                }
+                seen_skus.insert(sku.clone(), row.name.clone());
+            }
+            seen.insert(row.name.clone());
+
+            if exists {
+                summary.updated += 1;
+            } else {
+                summary.added += 1;
+            }
+            accepted.push(row);
+        }
+        if options.dry_run {
+            return Ok(summary);
+        }
+
+        for row in accepted {
+            match self.products.iter_mut().find(|p| p.name == row.name) {
+                Some(product) => {
+                    if let Some(description) = row.description {
+                        product.description = description;
+                    }
+                    if let Some(category) = row.category {
+                        product.category = category;
+                    }
+                    if let Some(sku) = row.sku {
+                        product.sku = sku;
+                    }
+                    if let Some(reorder_level) = row.reorder_level {
+                        product.reorder_level = reorder_level;
+                    }
+                    let price_changed = row.price != product.price;
+                    let change = row.quantity as i64 - product.quantity as i64;
+                    if price_changed {
+                        self.change_price(&row.name, row.price, &options.user)?;
+                    }
+                    if change != 0 {
+                        self.adjust_stock(&row.name, change, AdjustmentReason::CountCorrection, &options.user)?;
+                    }
+                }
+                None => {
+                    let mut product = Product::new(row.name, row.description.unwrap_or_default(), row.price, row.quantity);
+                    product.category = row.category.unwrap_or_default();
+                    product.sku = row.sku.unwrap_or_default();
+                    product.reorder_level = row.reorder_level.unwrap_or_default();
+                    product.created = Some(self.clock.now());
+                    self.products.push(product);
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    fn import_sales<R: Read>(
+        &mut self,
+        reader: R,
+        format: FileFormat,
+        options: &ImportOptions,
+    ) -> Result<ImportSummary, InventoryError> {
+        let mut summary = ImportSummary::default();
+        // Stock left after the rows so far, kept apart from the products so a
+        // dry run checks each row against the same levels a real import would.
+        let mut stock: HashMap<String, u32> = HashMap::new();
+        for row in read_rows(reader, format, options)? {
+            let receipt_id = self.next_receipt_id();
+            let sale = match parse_transaction_row(self, &row) {
+                Ok(sale) => sale,
+                Err(error) => {
+                    summary.errors.push(ImportRowError { line: row.line, error });
+                    continue;
+                }
+            };
+            if options.update_stock {
+                let on_hand = stock.entry(sale.product_name.clone()).or_insert_with(|| {
+                    self.products.iter().find(|p| p.name == sale.product_name).map_or(0, |p| p.quantity)
+                });
+                if *on_hand < sale.quantity {
+                    summary.errors.push(ImportRowError {
+                        line: row.line,
+                        error: InventoryError::OutOfStock(sale.product_name),
+                    });
+                    continue;
+                }
+                *on_hand -= sale.quantity;
+            }
+
+            summary.added += 1;
+            if options.dry_run {
+                continue;
+            }
+            if let Some(&quantity) = stock.get(&sale.product_name) {
+                let product = self.products.iter_mut().find(|p| p.name == sale.product_name).unwrap();
+                product.quantity = quantity;
+            }
+            let total_sale = sale.price * sale.quantity as f64;
+            // Old sales are costed at what the stock had cost by then, not at today's price.
+            let profit = total_sale - self.unit_cost_at(&sale.product_name, sale.timestamp.date()) * sale.quantity as f64;
+            self.sales.push(Sale {
+                id: self.sales.len() as u32 + 1,
+                product_name: sale.product_name,
+                quantity_sold: sale.quantity,
+                sale_price: sale.price,
+                total_sale,
+                profit,
+                timestamp: sale.timestamp,
+                receipt_id,
+            });
+        }
+        Ok(summary)
+    }
+
+    fn import_purchases<R: Read>(
+        &mut self,
+        reader: R,
+        format: FileFormat,
+        options: &ImportOptions,
+    ) -> Result<ImportSummary, InventoryError> {
+        let mut summary = ImportSummary::default();
+        for row in read_rows(reader, format, options)? {
+            let purchase = match parse_transaction_row(self, &row) {
+                Ok(purchase) => purchase,
+                Err(error) => {
+                    summary.errors.push(ImportRowError { line: row.line, error });
+                    continue;
+                }
+            };
+
+            summary.added += 1;
+            if options.dry_run {
+                continue;
+            }
+            if options.update_stock {
+                let product = self.products.iter_mut().find(|p| p.name == purchase.product_name).unwrap();
+                product.quantity += purchase.quantity;
+            }
+            self.purchases.push(Purchase {
+                id: self.purchases.len() as u32 + 1,
+                supplier: purchase.supplier,
+                product_name: purchase.product_name,
+                quantity_purchased: purchase.quantity,
+                purchase_price: purchase.price,
+                total_cost: purchase.price * purchase.quantity as f64,
+                timestamp: purchase.timestamp,
+            });
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::inventory::InventoryManagement;
+    use crate::purchases::PurchaseManagement;
+
+    const CATALOG: &str = "Name,Description,Price,Quantity,Category
+Laptop,\"Fast, light laptop\",1200,10,Computers
+Mouse,Wireless mouse,25.5,40,
+Cable,USB cable,abc,5,
+Stand,Monitor stand,30,0,
+,Nameless,5,5,
+Laptop,Duplicate row,1100,3,
+";
+
+    #[test]
+    fn test_import_products_reports_row_errors() {
+        let mut inventory = Inventory::new();
+
+        let summary = inventory.import_products(CATALOG.as_bytes(), &ImportOptions::default()).unwrap();
+
+        assert_eq!(summary.added, 2);
+        assert_eq!(
+            summary.errors,
+            vec![
+                ImportRowError {
+                    line: 4,
+                    error: InventoryError::InvalidInput("'abc' is not a valid price".to_string())
+                },
+                ImportRowError { line: 5, error: InventoryError::InvalidQuantity(0) },
+                ImportRowError {
+                    line: 6,
+                    error: InventoryError::InvalidInput("Product name cannot be empty".to_string())
+                },
+                ImportRowError { line: 7, error: InventoryError::DuplicateProduct("Laptop".to_string()) },
+            ]
+        );
+        assert_eq!(inventory.products.len(), 2);
+        assert_eq!(inventory.products[0].description, "Fast, light laptop");
+        assert_eq!(inventory.products[0].category, "Computers");
+    }
+
+    #[test]
+    fn test_import_products_rejects_non_finite_price() {
+        let mut inventory = Inventory::new();
+        let csv = "Name,Description,Price,Quantity\nLaptop,Laptop,inf,1\nMouse,Mouse,NaN,1\n";
+
+        let summary = inventory.import_products(csv.as_bytes(), &ImportOptions::default()).unwrap();
+
+        assert_eq!(summary.added, 0);
+        assert_eq!(
+            summary.errors,
+            vec![
+                ImportRowError { line: 2, error: InventoryError::InvalidInput("'inf' is not a valid price".to_string()) },
+                ImportRowError { line: 3, error: InventoryError::InvalidInput("'NaN' is not a valid price".to_string()) },
+            ]
+        );
+        assert!(inventory.products.is_empty());
+    }
+
+    #[test]
+    fn test_import_products_dry_run_changes_nothing() {
+        let mut inventory = Inventory::new();
+        let options = ImportOptions { dry_run: true, ..ImportOptions::default() };
+
+        let summary = inventory.import_products(CATALOG.as_bytes(), &options).unwrap();
+
+        assert_eq!(summary.added, 2);
+        assert_eq!(summary.errors.len(), 4);
+        assert!(inventory.products.is_empty());
+    }
+
+    #[test]
+    fn test_import_products_upsert() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Mouse".to_string(), "Old mouse".to_string(), 20.0, 5).unwrap();
+        inventory.set_product_category("Mouse", "Accessories".to_string()).unwrap();
+        let csv = "name,price,quantity\nMouse,22,12\nKeyboard,45,8\n";
+        let options = ImportOptions { upsert: true, user: "clerk".to_string(), ..ImportOptions::default() };
+
+        let summary = inventory.import_products(csv.as_bytes(), &options).unwrap();
+
+        assert_eq!((summary.added, summary.updated), (1, 1));
+        assert_eq!(inventory.products.len(), 2);
+        let mouse = &inventory.products[0];
+        assert_eq!((mouse.price, mouse.quantity), (22.0, 12));
+        assert_eq!(mouse.description, "Old mouse"); // columns missing from the file are kept
+        assert_eq!(mouse.category, "Accessories");
+        let adjustment = &inventory.adjustments[0];
+        assert_eq!((adjustment.quantity_change, adjustment.reason), (7, AdjustmentReason::CountCorrection));
+        assert_eq!(adjustment.user, "clerk");
+        let price_change = &inventory.price_changes[0];
+        assert_eq!((price_change.previous_price, price_change.price), (20.0, 22.0));
+        assert_eq!(price_change.user, "clerk");
+    }
+
+    #[test]
+    fn test_import_products_unreadable_record_applies_nothing() {
+        let mut inventory = Inventory::new();
+        let csv = b"name,price,quantity\nLamp,19.99,4\nBulb,\xff,50\n";
+
+        let result = inventory.import_products(&csv[..], &ImportOptions::default());
+
+        assert!(matches!(result, Err(InventoryError::InvalidInput(_))));
+        assert!(inventory.products.is_empty());
+    }
+
+    #[test]
+    fn test_import_products_column_map() {
+        let mut inventory = Inventory::new();
+        let csv = "Item,Retail,On Hand\nLamp,19.99,4\n";
+        let mut options = ImportOptions::default();
+        options.column_map.insert("name".to_string(), "Item".to_string());
+        options.column_map.insert("price".to_string(), "Retail".to_string());
+        options.column_map.insert("quantity".to_string(), "On Hand".to_string());
+
+        inventory.import_products(csv.as_bytes(), &options).unwrap();
+        assert_eq!(inventory.products[0].name, "Lamp");
+
+        let result = inventory.import_products(csv.as_bytes(), &ImportOptions::default());
+        assert_eq!(result, Err(InventoryError::InvalidInput("Missing column 'name'".to_string())));
+    }
+
+    #[test]
+    fn test_import_products_sku_and_reorder_level() {
+        let mut inventory = Inventory::new();
+        let csv = "name,price,quantity,sku,reorder_level\nLamp,19.99,4,LMP-1,2\nBulb,3,50,LMP-1,\nShade,9,5,,x\n";
+
+        let summary = inventory.import_products(csv.as_bytes(), &ImportOptions::default()).unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(
+            summary.errors,
+            vec![
+                ImportRowError {
+                    line: 3,
+                    error: InventoryError::SkuInUse("LMP-1".to_string(), "Lamp".to_string())
+                },
+                ImportRowError {
+                    line: 4,
+                    error: InventoryError::InvalidInput("'x' is not a valid reorder level".to_string())
+                },
+            ]
+        );
+        assert_eq!((inventory.products[0].sku.as_str(), inventory.products[0].reorder_level), ("LMP-1", 2));
+    }
+
+    fn inventory_with_laptop() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_import_sales_csv_preserves_dates() {
+        let mut inventory = inventory_with_laptop();
+        let csv = "date,product,quantity,price\n2019-03-02,Laptop,2,1200\n2019-03-05 14:30,Tablet,1,300\n";
+
+        let summary = inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &ImportOptions::default()).unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(
+            summary.errors,
+            vec![ImportRowError { line: 3, error: InventoryError::ProductNotFound("Tablet".to_string()) }]
+        );
+        let sale = &inventory.sales[0];
+        assert_eq!(sale.timestamp.to_string(), "2019-03-02 00:00:00");
+        assert_eq!(sale.profit, 400.0);
+        assert_eq!(inventory.products[0].quantity, 10); // history leaves stock alone by default
+    }
+
+    #[test]
+    fn test_import_sales_updates_stock() {
+        let mut inventory = inventory_with_laptop();
+        let csv = "date,product,quantity,price\n2019-03-02,Laptop,4,1200\n2019-03-03,Laptop,7,1200\n";
+        let options = ImportOptions { update_stock: true, ..ImportOptions::default() };
+
+        let summary = inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &options).unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.errors[0].error, InventoryError::OutOfStock("Laptop".to_string()));
+        assert_eq!(inventory.products[0].quantity, 6);
+    }
+
+    #[test]
+    fn test_import_sales_dry_run_matches_import() {
+        let csv = "date,product,quantity,price\n2019-03-02,Laptop,6,1200\n2019-03-03,Laptop,6,1200\n";
+        let mut inventory = inventory_with_laptop();
+
+        let dry_run = ImportOptions { dry_run: true, update_stock: true, ..ImportOptions::default() };
+        let preview = inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &dry_run).unwrap();
+        let options = ImportOptions { update_stock: true, ..ImportOptions::default() };
+        let summary = inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &options).unwrap();
+
+        assert_eq!(preview, summary);
+        assert_eq!(summary.errors[0].line, 3);
+        assert_eq!(inventory.products[0].quantity, 4);
+    }
+
+    #[test]
+    fn test_import_sales_profit_uses_purchase_cost() {
+        let day = |d| NaiveDate::from_ymd_opt(2019, 3, d).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(day(1))));
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+        inventory.record_purchase("Laptop", 4, 900.0).unwrap();
+        let csv = "date,product,quantity,price\n2019-03-02,Laptop,2,1200\n";
+
+        inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &ImportOptions::default()).unwrap();
+
+        assert_eq!(inventory.sales[0].profit, 600.0);
+    }
+
+    #[test]
+    fn test_import_purchases_json() {
+        let mut inventory = inventory_with_laptop();
+        let json = r#"[
+            {"date": "2018-11-20T09:15:00", "product": "Laptop", "quantity": 5, "price": 950.0, "supplier": "Acme"},
+            {"date": "2018-11-21", "product": "Printer", "quantity": 1, "price": 150},
+            {"date": "yesterday", "product": "Laptop", "quantity": 1, "price": 950}
+        ]"#;
+        let options = ImportOptions { update_stock: true, ..ImportOptions::default() };
+
+        let summary = inventory.import_purchases(json.as_bytes(), FileFormat::Json, &options).unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(
+            summary.errors,
+            vec![
+                ImportRowError { line: 2, error: InventoryError::ProductNotFound("Printer".to_string()) },
+                ImportRowError {
+                    line: 3,
+                    error: InventoryError::InvalidInput("'yesterday' is not a valid date".to_string())
+                },
+            ]
+        );
+        let purchase = &inventory.purchases[0];
+        assert_eq!(purchase.supplier, "Acme");
+        assert_eq!(purchase.total_cost, 4750.0);
+        assert_eq!(purchase.timestamp.to_string(), "2018-11-20 09:15:00");
+        assert_eq!(inventory.products[0].quantity, 15);
+    }
+
+    #[test]
+    fn test_import_history_dry_run() {
+        let mut inventory = inventory_with_laptop();
+        let csv = "date,product,quantity,price\n2019-03-02,Laptop,2,1200\n";
+        let options = ImportOptions { dry_run: true, update_stock: true, ..ImportOptions::default() };
+
+        let summary = inventory.import_sales(csv.as_bytes(), FileFormat::Csv, &options).unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert!(inventory.sales.is_empty());
+        assert_eq!(inventory.products[0].quantity, 10);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::tui::TuiError;
+use chrono::NaiveDate;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// Pressing Esc and then Enter abandons the current form.
+pub const CANCEL_KEY: char = '\u{1b}';
+
+/// Reads one trimmed line after showing `prompt`. A line containing the cancel
+/// key gives `TuiError::Cancelled`; end of input is an error so the program stops
+/// instead of looping on empty answers.
+pub fn read_line(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> Result<String, TuiError> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input closed").into());
+    }
+    if line.contains(CANCEL_KEY) {
+        return Err(TuiError::Cancelled);
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Asks until `parse` accepts the answer, explaining each rejection.
+pub fn ask<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, TuiError> {
+    loop {
+        let line = read_line(input, output, prompt)?;
+        match parse(&line) {
+            Ok(value) => return Ok(value),
+            Err(message) => writeln!(output, "{} Please try again, or press Esc then Enter to cancel.", message)?,
+        }
+    }
+}
+
+pub fn parse_text(text: &str) -> Result<String, String> {
+    if text.is_empty() {
+        return Err("A value is required.".to_string());
+    }
+    Ok(text.to_string())
+}
+
+pub fn parse_whole_number<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("'{}' is not a valid whole number.", text))
+}
+
+/// Accepts either `.` or `,` as the decimal separator. When both appear, the last
+/// one is the decimal separator and the other groups thousands, so "1.234,50" and
+/// "1,234.50" are the same amount.
+pub fn parse_decimal(text: &str) -> Result<f64, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let normalized = match (compact.rfind('.'), compact.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => compact.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => compact.replace(',', ""),
+        (None, Some(_)) if compact.matches(',').count() > 1 => compact.replace(',', ""),
+        // "1,234" could be a thousand and more or one and a bit, so ask rather than guess.
+        (None, Some(comma)) if compact.len() - comma == 4 && compact[comma + 1..].chars().all(|c| c.is_ascii_digit()) => {
+            return Err(format!("'{}' is ambiguous; write it without the comma or with a period.", text))
+        }
+        (None, Some(_)) => compact.replace(',', "."),
+        _ => compact,
+    };
+    match normalized.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("'{}' is not a valid number.", text)),
+    }
+}
+
+pub fn parse_money(text: &str) -> Result<f64, String> {
+    let amount = parse_decimal(text.trim().trim_start_matches('$'))
+        .map_err(|_| format!("'{}' is not a valid amount.", text))?;
+    if amount < 0.0 {
+        return Err("Amounts cannot be negative.".to_string());
+    }
+    Ok(amount)
+}
+
+pub fn parse_yes_no(text: &str) -> Result<bool, String> {
+    match text.to_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err("Please answer y or n.".to_string()),
+    }
+}
+
+pub fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| format!("'{}' is not a date in YYYY-MM-DD form.", text))
+}
+
+/// The zero-based index of a numbered option from 1 to `count`.
+pub fn parse_choice(text: &str, count: usize) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(number) if (1..=count).contains(&number) => Ok(number - 1),
+        _ => Err(format!("Choose a number from 1 to {}.", count)),
+    }
+}
+
+/// A blank answer means "no value"; anything else must pass `parse`.
+pub fn parse_optional<T>(text: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    parse(text).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_decimal_separators() {
+        assert_eq!(parse_decimal("12.50"), Ok(12.5));
+        assert_eq!(parse_decimal("12,50"), Ok(12.5));
+        assert_eq!(parse_decimal("1.234,56"), Ok(1234.56));
+        assert_eq!(parse_decimal("1,234.56"), Ok(1234.56));
+        assert_eq!(parse_decimal("1,234,567"), Ok(1234567.0));
+        assert!(parse_decimal("1,234").is_err());
+        assert_eq!(parse_decimal("1,2345"), Ok(1.2345));
+        assert_eq!(parse_money("$ 3,5"), Ok(3.5));
+        assert!(parse_money("ten").is_err());
+        assert!(parse_money("-2").is_err());
+        assert!(parse_decimal("inf").is_err());
+    }
+
+    #[test]
+    fn test_parse_whole_number_and_choice() {
+        assert_eq!(parse_whole_number::<u32>("12"), Ok(12));
+        assert_eq!(parse_whole_number::<i64>("-3"), Ok(-3));
+        assert_eq!(parse_whole_number::<u32>("ten"), Err("'ten' is not a valid whole number.".to_string()));
+        assert!(parse_whole_number::<u32>("-3").is_err());
+        assert_eq!(parse_choice("2", 3), Ok(1));
+        assert!(parse_choice("4", 3).is_err());
+        assert!(parse_choice("0", 3).is_err()); // a menu never has an option 0
+        assert!(parse_choice("", 3).is_err());
+        assert_eq!(parse_yes_no("YES"), Ok(true));
+        assert_eq!(parse_optional("", parse_money), Ok(None));
+    }
+
+    #[test]
+    fn test_ask_reprompts_until_valid() {
+        let mut input = Cursor::new("ten\n\n12,5\n");
+        let mut output = Vec::new();
+
+        let price = ask(&mut input, &mut output, "Price: ", parse_money).unwrap();
+        assert_eq!(price, 12.5);
+        let shown = String::from_utf8(output).unwrap();
+        assert_eq!(shown.matches("Price: ").count(), 3);
+        assert!(shown.contains("'ten' is not a valid amount."));
+    }
+
+    #[test]
+    fn test_ask_cancel_and_end_of_input() {
+        let mut output = Vec::new();
+
+        let result = ask(&mut Cursor::new("\u{1b}\n"), &mut output, "Quantity: ", parse_whole_number::<u32>);
+        assert!(matches!(result, Err(TuiError::Cancelled)));
+        let result = ask(&mut Cursor::new(""), &mut output, "Quantity: ", parse_whole_number::<u32>);
+        assert!(matches!(result, Err(TuiError::Io(_))));
+    }
+}
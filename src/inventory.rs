@@ -1,5 +1,14 @@
+use crate::abc::AbcClass;
+use crate::adjustments::StockAdjustment;
+use crate::clock::{Clock, SystemClock};
+use crate::pricing::{PriceChange, PriceManagement};
 use crate::purchases::Purchase;
+use crate::returns::SaleReturn;
 use crate::sales::Sale;
+use crate::stock_take::StockTake;
+use crate::supplier_returns::SupplierReturn;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, PartialEq)]
@@ -12,6 +21,14 @@ pub enum InventoryError {
     InvalidInput(String),
     PurchaseFailed(String),
     SaleFailed(String),
+    ReturnFailed(String),
+    SupplierReturnFailed(String),
+    StockTakeFailed(String),
+    DuplicateProduct(String),
+    SkuInUse(String, String),
+    ProductHasTransactions(String),
+    StorageFailed(String),
+    UndoFailed(String),
 }
 
 impl fmt::Display for InventoryError {
@@ -20,10 +37,20 @@ impl fmt::Display for InventoryError {
             InventoryError::ProductNotFound(name) => write!(f, "Product '{}' not found", name),
             InventoryError::InvalidQuantity(qty) => write!(f, "Invalid quantity: {}", qty),
             InventoryError::InvalidPrice(price) => write!(f, "Invalid price: {}", price),
-            InventoryError::OutOfStock(reason) => write!(f, "Purchase failed: {}", reason),
-            InventoryError::InvalidInput(reason) => write!(f, "Purchase failed: {}", reason),
+            InventoryError::OutOfStock(reason) => write!(f, "Not enough stock of {}", reason),
+            InventoryError::InvalidInput(reason) => write!(f, "Invalid input: {}", reason),
             InventoryError::PurchaseFailed(reason) => write!(f, "Purchase failed: {}", reason),
             InventoryError::SaleFailed(reason) => write!(f, "Sale failed: {}", reason),
+            InventoryError::ReturnFailed(reason) => write!(f, "Return failed: {}", reason),
+            InventoryError::SupplierReturnFailed(reason) => write!(f, "Supplier return failed: {}", reason),
+            InventoryError::StockTakeFailed(reason) => write!(f, "Stock take failed: {}", reason),
+            InventoryError::DuplicateProduct(name) => write!(f, "Product '{}' already exists", name),
+            InventoryError::SkuInUse(sku, owner) => write!(f, "SKU '{}' is already used by '{}'", sku, owner),
+            InventoryError::ProductHasTransactions(name) => {
+                write!(f, "Product '{}' has recorded transactions and can only be archived", name)
+            }
+            InventoryError::StorageFailed(reason) => write!(f, "Storage failed: {}", reason),
+            InventoryError::UndoFailed(reason) => write!(f, "Cannot undo or redo: {}", reason),
         }
     }
 }
@@ -39,30 +66,94 @@ pub trait InventoryManagement {
         new_description: Option<String>,
         new_price: Option<f64>,
         new_quantity: Option<u32>,
+        user: &str,
     ) -> Result<(), InventoryError>;
     fn delete_product(&mut self, product_name: &str) -> Result<(), InventoryError>;
-    fn list_products(&self);
+    fn archive_product(&mut self, product_name: &str) -> Result<(), InventoryError>;
+    fn restore_product(&mut self, product_name: &str) -> Result<(), InventoryError>;
+    fn set_product_category(&mut self, product_name: &str, category: String) -> Result<(), InventoryError>;
+    fn set_product_sku(&mut self, product_name: &str, sku: String) -> Result<(), InventoryError>;
+    fn set_reorder_level(&mut self, product_name: &str, reorder_level: u32) -> Result<(), InventoryError>;
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Product {
     pub name: String,
     pub description: String,
     pub price: f64,
     pub quantity: u32,
+    pub category: String,
+    /// Stock keeping unit or barcode. Empty when not assigned; otherwise unique.
+    pub sku: String,
+    /// Stock level at or below which the product should be reordered; 0 disables it.
+    pub reorder_level: u32,
+    pub abc_class: Option<AbcClass>,
+    /// Archived products are kept for their history but hidden from listings
+    /// and cannot be sold.
+    #[serde(default)]
+    pub archived: bool,
+    /// When the product was first added; unknown for products saved before
+    /// this was recorded.
+    #[serde(default)]
+    pub created: Option<NaiveDateTime>,
+}
+
+impl Product {
+    pub fn new(name: String, description: String, price: f64, quantity: u32) -> Self {
+        Product {
+            name,
+            description,
+            price,
+            quantity,
+            category: String::new(),
+            sku: String::new(),
+            reorder_level: 0,
+            abc_class: None,
+            archived: false,
+            created: None,
+        }
+    }
+
+    pub fn is_below_reorder_level(&self) -> bool {
+        self.reorder_level > 0 && self.quantity <= self.reorder_level
+    }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Inventory {
     pub products: Vec<Product>,
     pub sales: Vec<Sale>,
     pub purchases: Vec<Purchase>,
+    pub returns: Vec<SaleReturn>,
+    pub supplier_returns: Vec<SupplierReturn>,
+    pub adjustments: Vec<StockAdjustment>,
+    pub stock_takes: Vec<StockTake>,
+    #[serde(default)]
+    pub price_changes: Vec<PriceChange>,
+    #[serde(skip, default = "system_clock")]
+    pub clock: Box<dyn Clock>,
+}
+
+fn system_clock() -> Box<dyn Clock> {
+    Box::new(SystemClock)
 }
 
 impl Inventory {
     pub fn new() -> Self {
+        Inventory::with_clock(Box::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
         Inventory {
             products: Vec::new(),
             sales: Vec::new(),
             purchases: Vec::new(),
+            returns: Vec::new(),
+            supplier_returns: Vec::new(),
+            adjustments: Vec::new(),
+            stock_takes: Vec::new(),
+            price_changes: Vec::new(),
+            clock,
         }
     }
     /*
@@ -119,6 +210,49 @@ impl Inventory {
             }
         }
     */
+
+    /// Whether any sale, purchase, return, adjustment or stock take refers to the product.
+    pub fn has_transactions(&self, product_name: &str) -> bool {
+        self.sales.iter().any(|s| s.product_name == product_name)
+            || self.purchases.iter().any(|p| p.product_name == product_name)
+            || self.returns.iter().any(|r| r.product_name == product_name)
+            || self.supplier_returns.iter().any(|r| r.product_name == product_name)
+            || self.adjustments.iter().any(|a| a.product_name == product_name)
+            || self
+                .stock_takes
+                .iter()
+                .any(|t| t.lines.iter().any(|l| l.product_name == product_name))
+    }
+
+    /// Fails when another product already has the SKU. Forms that set a SKU
+    /// alongside other fields check it first, so a taken SKU changes nothing.
+    pub(crate) fn check_sku_available(&self, product_name: &str, sku: &str) -> Result<(), InventoryError> {
+        let sku = sku.trim();
+        match self.products.iter().find(|p| !sku.is_empty() && p.sku == sku && p.name != product_name) {
+            Some(other) => Err(InventoryError::SkuInUse(sku.to_string(), other.name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Renames a product along with every record that refers to it, so its
+    /// history follows it.
+    pub(crate) fn rename_product(&mut self, old_name: &str, new_name: &str) {
+        let rename = |name: &mut String| {
+            if name == old_name {
+                *name = new_name.to_string();
+            }
+        };
+        self.products.iter_mut().for_each(|p| rename(&mut p.name));
+        self.sales.iter_mut().for_each(|s| rename(&mut s.product_name));
+        self.purchases.iter_mut().for_each(|p| rename(&mut p.product_name));
+        self.returns.iter_mut().for_each(|r| rename(&mut r.product_name));
+        self.supplier_returns.iter_mut().for_each(|r| rename(&mut r.product_name));
+        self.adjustments.iter_mut().for_each(|a| rename(&mut a.product_name));
+        self.price_changes.iter_mut().for_each(|c| rename(&mut c.product_name));
+        for stock_take in &mut self.stock_takes {
+            stock_take.lines.iter_mut().for_each(|l| rename(&mut l.product_name));
+        }
+    }
 }
 
 impl InventoryManagement for Inventory {
@@ -131,15 +265,9 @@ impl InventoryManagement for Inventory {
             return Err(InventoryError::InvalidQuantity(quantity));
         }
 
-        let product = Product {
-            name,
-            description,
-            price,
-            quantity,
-        };
+        let mut product = Product::new(name, description, price, quantity);
+        product.created = Some(self.clock.now());
         self.products.push(product);
-
-        println!("Product added successfully.");
         Ok(())
     }
 
@@ -150,44 +278,117 @@ impl InventoryManagement for Inventory {
         new_description: Option<String>,
         new_price: Option<f64>,
         new_quantity: Option<u32>,
+        user: &str,
     ) -> Result<(), InventoryError> {
-        for product in &mut self.products {
-            if product.name == product_name {
-                if let Some(name) = new_name {
-                    product.name = name;
-                }
-                if let Some(description) = new_description {
-                    product.description = description;
-                }
-                if let Some(price) = new_price {
-                    product.price = price;
-                }
-                if let Some(quantity) = new_quantity {
-                    product.quantity = quantity;
-                }
-                println!("Product updated successfully.");
-                return Ok(())
+        let index = match self.products.iter().position(|p| p.name == product_name) {
+            Some(index) => index,
+            None => return Err(InventoryError::ProductNotFound(product_name.to_string())),
+        };
+
+        // Everything is checked before anything changes, with the same rules as add_product.
+        if let Some(price) = new_price {
+            if price <= 0.0 {
+                return Err(InventoryError::InvalidPrice(price));
+            }
+        }
+        if new_quantity == Some(0) {
+            return Err(InventoryError::InvalidQuantity(0));
+        }
+        if let Some(name) = &new_name {
+            if name.trim().is_empty() {
+                return Err(InventoryError::InvalidInput("product name cannot be empty".to_string()));
+            }
+            if name != product_name && self.products.iter().any(|p| &p.name == name) {
+                return Err(InventoryError::DuplicateProduct(name.clone()));
             }
         }
-        return Err(InventoryError::ProductNotFound(product_name.to_string()));
+
+        let product = &mut self.products[index];
+        if let Some(description) = new_description {
+            product.description = description;
+        }
+        if let Some(quantity) = new_quantity {
+            product.quantity = quantity;
+        }
+        if let Some(price) = new_price.filter(|&price| price != self.products[index].price) {
+            self.change_price(product_name, price, user)?;
+        }
+        if let Some(name) = new_name {
+            self.rename_product(product_name, &name);
+        }
+        Ok(())
     }
 
+    // Only products nothing refers to can be deleted; anything with history is
+    // archived instead so reports never show sales of an unknown product.
     fn delete_product(&mut self, product_name: &str) -> Result<(), InventoryError> {
+        if self.has_transactions(product_name) {
+            return Err(InventoryError::ProductHasTransactions(product_name.to_string()));
+        }
         if let Some(pos) = self.products.iter().position(|p| p.name == product_name) {
             self.products.remove(pos);
-            println!("Product deleted successfully.");
+            self.price_changes.retain(|c| c.product_name != product_name);
             Ok(())
         } else {
-            return Err(InventoryError::ProductNotFound(product_name.to_string()));
+            Err(InventoryError::ProductNotFound(product_name.to_string()))
+        }
+    }
+
+    fn archive_product(&mut self, product_name: &str) -> Result<(), InventoryError> {
+        match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) if product.archived => {
+                Err(InventoryError::InvalidInput(format!("'{}' is already archived", product_name)))
+            }
+            Some(product) => {
+                product.archived = true;
+                Ok(())
+            }
+            None => Err(InventoryError::ProductNotFound(product_name.to_string())),
+        }
+    }
+
+    fn restore_product(&mut self, product_name: &str) -> Result<(), InventoryError> {
+        match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) if !product.archived => {
+                Err(InventoryError::InvalidInput(format!("'{}' is not archived", product_name)))
+            }
+            Some(product) => {
+                product.archived = false;
+                Ok(())
+            }
+            None => Err(InventoryError::ProductNotFound(product_name.to_string())),
         }
     }
 
-    fn list_products(&self) {
-        for product in &self.products {
-            println!(
-                "Name: {}, Description: {}, Price: ${}, Quantity: {}",
-                product.name, product.description, product.price, product.quantity
-            );
+    fn set_product_category(&mut self, product_name: &str, category: String) -> Result<(), InventoryError> {
+        match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) => {
+                product.category = category;
+                Ok(())
+            }
+            None => Err(InventoryError::ProductNotFound(product_name.to_string())),
+        }
+    }
+
+    fn set_product_sku(&mut self, product_name: &str, sku: String) -> Result<(), InventoryError> {
+        let sku = sku.trim().to_string();
+        self.check_sku_available(product_name, &sku)?;
+        match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) => {
+                product.sku = sku;
+                Ok(())
+            }
+            None => Err(InventoryError::ProductNotFound(product_name.to_string())),
+        }
+    }
+
+    fn set_reorder_level(&mut self, product_name: &str, reorder_level: u32) -> Result<(), InventoryError> {
+        match self.products.iter_mut().find(|p| p.name == product_name) {
+            Some(product) => {
+                product.reorder_level = reorder_level;
+                Ok(())
+            }
+            None => Err(InventoryError::ProductNotFound(product_name.to_string())),
         }
     }
 }
@@ -237,6 +438,7 @@ mod tests {
             Some("A high-end gaming laptop".to_string()),
             Some(1500.0),
             Some(5),
+            "manager",
         ) {
             Ok(()) => println!("Product edited successfully."),
             Err(e) => eprintln!("Failed to remove product: {}", e),
@@ -249,6 +451,32 @@ mod tests {
         assert_eq!(product.quantity, 5);
     }
 
+    #[test]
+    fn test_edit_product_validation() {
+        use crate::sales::SalesManagement;
+
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "A high-performance laptop".to_string(), 1200.0, 10).unwrap();
+        inventory.add_product("Tablet".to_string(), "A 10-inch tablet".to_string(), 400.0, 10).unwrap();
+        inventory.record_sale("Laptop", 1, 1500.0).unwrap();
+
+        assert_eq!(
+            inventory.edit_product("Laptop", None, None, Some(-5.0), None, "manager"),
+            Err(InventoryError::InvalidPrice(-5.0))
+        );
+        assert_eq!(
+            inventory.edit_product("Laptop", Some("Tablet".to_string()), None, Some(1300.0), None, "manager"),
+            Err(InventoryError::DuplicateProduct("Tablet".to_string()))
+        );
+        assert_eq!(inventory.products[0].price, 1200.0); // a rejected edit changes nothing
+        assert!(inventory.edit_product("Laptop", Some(" ".to_string()), None, None, None, "manager").is_err());
+        assert!(inventory.edit_product("Laptop", None, None, None, Some(0), "manager").is_err());
+
+        inventory.edit_product("Laptop", Some("Notebook".to_string()), None, None, None, "manager").unwrap();
+        assert_eq!(inventory.products[0].description, "A high-performance laptop");
+        assert_eq!(inventory.sales[0].product_name, "Notebook");
+    }
+
     #[test]
     fn test_delete_product() {
         let mut inventory = Inventory::new();
@@ -272,6 +500,60 @@ mod tests {
         assert_eq!(inventory.products.len(), 0);
     }
 
+    #[test]
+    fn test_archive_product_with_history() {
+        use crate::sales::SalesManagement;
+        use crate::search::{ProductFilter, ProductSearch};
+
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "A high-performance laptop".to_string(), 1200.0, 10).unwrap();
+        inventory.record_sale("Laptop", 1, 1500.0).unwrap();
+
+        assert_eq!(
+            inventory.delete_product("Laptop"),
+            Err(InventoryError::ProductHasTransactions("Laptop".to_string()))
+        );
+        inventory.archive_product("Laptop").unwrap();
+        assert!(inventory.archive_product("Laptop").is_err());
+        assert!(inventory.search_products(&ProductFilter::default()).is_empty());
+        assert!(matches!(inventory.record_sale("Laptop", 1, 1500.0), Err(InventoryError::SaleFailed(_))));
+
+        inventory.restore_product("Laptop").unwrap();
+        inventory.record_sale("Laptop", 1, 1500.0).unwrap();
+        assert_eq!(inventory.products[0].quantity, 8);
+        assert!(inventory.restore_product("Laptop").is_err());
+    }
+
+    #[test]
+    fn test_set_product_category() {
+        let mut inventory = Inventory::new();
+        inventory
+            .add_product("Laptop".to_string(), "A high-performance laptop".to_string(), 1200.0, 10)
+            .unwrap();
+
+        assert!(inventory.set_product_category("Laptop", "Computers".to_string()).is_ok());
+        assert_eq!(inventory.products[0].category, "Computers");
+        assert_eq!(
+            inventory.set_product_category("Tablet", "Computers".to_string()),
+            Err(InventoryError::ProductNotFound("Tablet".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_set_product_sku() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "A high-performance laptop".to_string(), 1200.0, 10).unwrap();
+        inventory.add_product("Tablet".to_string(), "A 10-inch tablet".to_string(), 400.0, 10).unwrap();
+
+        inventory.set_product_sku("Laptop", " LAP-1 ".to_string()).unwrap();
+        assert_eq!(inventory.products[0].sku, "LAP-1");
+        assert_eq!(
+            inventory.set_product_sku("Tablet", "LAP-1".to_string()),
+            Err(InventoryError::SkuInUse("LAP-1".to_string(), "Laptop".to_string()))
+        );
+        assert!(inventory.set_product_sku("Tablet", String::new()).is_ok());
+    }
+
     #[test]
     fn test_list_products() {
         let mut inventory = Inventory::new();
@@ -1,14 +1,37 @@
+mod abc;
+mod adjustments;
 mod auth;
+mod batch;
+mod checkout;
+mod cli;
+mod clock;
+mod forecast;
+mod history;
 mod tui;
+mod import;
+mod input;
 mod inventory;
+mod pricing;
 mod purchases;
 mod sales;
+mod stock_take;
+mod returns;
+mod screen;
+mod search;
+mod storage;
+mod supplier_returns;
+mod valuation;
 mod reporting;
+mod render;
 
 use crate::auth::Auth;
-use crate::tui::Tui;
+use crate::tui::{Tui, TuiError};
+use std::io;
+use std::process::ExitCode;
 
-fn main() {
+// With arguments the program runs a single command for scripts; without any
+// it starts the interactive interface.
+fn main() -> ExitCode {
     let mut auth_system = Auth::new();
     auth_system.users.insert(
         "admin".to_string(),
@@ -19,12 +42,29 @@ fn main() {
         },
     );
 
-    let mut tui = Tui::new(auth_system);
-    if let Err(e) = tui.run() {
-        eprintln!("Error: {}", e);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let env = |name: &str| std::env::var(name).ok();
+    let data_path = match cli::interactive_data_path(&args, &env) {
+        Some(path) => path,
+        None => {
+            return match cli::run(&args, &auth_system, &env, &mut io::stdout()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::from(e.exit_code())
+                }
+            };
+        }
+    };
+
+    let mut tui = Tui::new(auth_system, data_path);
+    match tui.run() {
+        Ok(()) | Err(TuiError::Cancelled) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
     }
-
-
 }
 /*
 mod inventory;
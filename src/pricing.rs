@@ -0,0 +1,211 @@
+use crate::inventory::{Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A change to a product's price. Without `until` it replaces the regular
+/// price from `effective` on; with `until` it is a temporary sale price that
+/// applies from `effective` up to, but not including, `until`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceChange {
+    pub product_name: String,
+    pub price: f64,
+    /// The regular price the change replaced, so earlier prices can be looked up.
+    pub previous_price: f64,
+    pub effective: NaiveDateTime,
+    pub until: Option<NaiveDateTime>,
+    pub user: String,
+    pub recorded: NaiveDateTime,
+}
+
+impl PriceChange {
+    pub fn applies_at(&self, at: NaiveDateTime) -> bool {
+        self.effective <= at && self.until.is_none_or(|until| at < until)
+    }
+
+    pub fn is_sale_price(&self) -> bool {
+        self.until.is_some()
+    }
+}
+
+#[allow(dead_code)]
+pub trait PriceManagement {
+    /// Changes the regular price from now on.
+    fn change_price(&mut self, product_name: &str, price: f64, user: &str) -> Result<(), InventoryError>;
+    /// Schedules a regular price change, or with `until` a temporary sale price.
+    fn schedule_price(
+        &mut self,
+        product_name: &str,
+        price: f64,
+        effective: NaiveDateTime,
+        until: Option<NaiveDateTime>,
+        user: &str,
+    ) -> Result<(), InventoryError>;
+    fn price_at(&self, product_name: &str, at: NaiveDateTime) -> Option<f64>;
+    fn current_price(&self, product_name: &str) -> Option<f64>;
+    /// Brings each product's listed price up to date with the scheduled
+    /// changes that have come due. Returns how many prices changed.
+    fn apply_due_prices(&mut self) -> usize;
+}
+
+impl Inventory {
+    // Before its first recorded change, a product sold at the price that change replaced.
+    fn regular_price_at(&self, product_name: &str, at: NaiveDateTime) -> Option<f64> {
+        let product = self.products.iter().find(|p| p.name == product_name)?;
+        let regular = self
+            .price_changes
+            .iter()
+            .filter(|c| c.product_name == product_name && !c.is_sale_price());
+        match regular.clone().filter(|c| c.effective <= at).max_by_key(|c| c.effective) {
+            Some(change) => Some(change.price),
+            None => Some(regular.min_by_key(|c| c.effective).map_or(product.price, |c| c.previous_price)),
+        }
+    }
+
+    fn push_price_change(
+        &mut self,
+        product_name: &str,
+        price: f64,
+        effective: NaiveDateTime,
+        until: Option<NaiveDateTime>,
+        user: &str,
+    ) -> Result<(), InventoryError> {
+        if price <= 0.0 {
+            return Err(InventoryError::InvalidPrice(price));
+        }
+        let previous_price = match self.regular_price_at(product_name, effective) {
+            Some(price) => price,
+            None => return Err(InventoryError::ProductNotFound(product_name.to_string())),
+        };
+        self.price_changes.push(PriceChange {
+            product_name: product_name.to_string(),
+            price,
+            previous_price,
+            effective,
+            until,
+            user: user.to_string(),
+            recorded: self.clock.now(),
+        });
+        Ok(())
+    }
+}
+
+impl PriceManagement for Inventory {
+    fn change_price(&mut self, product_name: &str, price: f64, user: &str) -> Result<(), InventoryError> {
+        let now = self.clock.now();
+        self.push_price_change(product_name, price, now, None, user)?;
+        self.apply_due_prices();
+        Ok(())
+    }
+
+    fn schedule_price(
+        &mut self,
+        product_name: &str,
+        price: f64,
+        effective: NaiveDateTime,
+        until: Option<NaiveDateTime>,
+        user: &str,
+    ) -> Result<(), InventoryError> {
+        let now = self.clock.now();
+        match until {
+            Some(until) if until <= effective => {
+                return Err(InventoryError::InvalidInput("a sale price must end after it starts".to_string()))
+            }
+            Some(until) if until <= now => {
+                return Err(InventoryError::InvalidInput("a sale price must end in the future".to_string()))
+            }
+            None if effective <= now => {
+                return Err(InventoryError::InvalidInput(
+                    "a scheduled price change must start in the future; change the price now instead".to_string(),
+                ))
+            }
+            _ => {}
+        }
+        self.push_price_change(product_name, price, effective, until, user)?;
+        self.apply_due_prices();
+        Ok(())
+    }
+
+    // A sale price in effect wins over the regular price. Among overlapping
+    // ones, the one that started last applies.
+    fn price_at(&self, product_name: &str, at: NaiveDateTime) -> Option<f64> {
+        self.price_changes
+            .iter()
+            .filter(|c| c.product_name == product_name && c.is_sale_price() && c.applies_at(at))
+            .max_by_key(|c| c.effective)
+            .map(|c| c.price)
+            .or_else(|| self.regular_price_at(product_name, at))
+    }
+
+    fn current_price(&self, product_name: &str) -> Option<f64> {
+        self.price_at(product_name, self.clock.now())
+    }
+
+    fn apply_due_prices(&mut self) -> usize {
+        let now = self.clock.now();
+        let due: Vec<Option<f64>> = self.products.iter().map(|p| self.regular_price_at(&p.name, now)).collect();
+        let mut changed = 0;
+        for (product, price) in self.products.iter_mut().zip(due) {
+            if let Some(price) = price.filter(|&price| price != product.price) {
+                product.price = price;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+    use chrono::NaiveDate;
+
+    fn at(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn inventory_on(day: u32) -> Inventory {
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(at(day))));
+        inventory.add_product("Lamp".to_string(), "Desk lamp".to_string(), 20.0, 10).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_price_history() {
+        let mut inventory = inventory_on(5);
+        inventory.edit_product("Lamp", None, None, Some(22.0), None, "manager").unwrap();
+        inventory.edit_product("Lamp", None, None, Some(22.0), None, "manager").unwrap();
+
+        assert_eq!(inventory.price_changes.len(), 1); // an unchanged price is not a change
+        let change = &inventory.price_changes[0];
+        assert_eq!((change.previous_price, change.price, change.user.as_str()), (20.0, 22.0, "manager"));
+        assert_eq!(inventory.price_at("Lamp", at(1)), Some(20.0));
+        assert_eq!(inventory.price_at("Lamp", at(5)), Some(22.0));
+        assert_eq!(inventory.price_at("Mug", at(5)), None);
+        assert_eq!(inventory.change_price("Lamp", 0.0, "manager"), Err(InventoryError::InvalidPrice(0.0)));
+    }
+
+    #[test]
+    fn test_scheduled_and_sale_prices() {
+        let mut inventory = inventory_on(1);
+        inventory.schedule_price("Lamp", 25.0, at(10), None, "manager").unwrap();
+        inventory.schedule_price("Lamp", 15.0, at(3), Some(at(5)), "manager").unwrap();
+        assert!(inventory.schedule_price("Lamp", 25.0, at(1), None, "manager").is_err());
+        assert!(inventory.schedule_price("Lamp", 15.0, at(5), Some(at(3)), "manager").is_err());
+
+        assert_eq!(inventory.products[0].price, 20.0);
+        assert_eq!(inventory.price_at("Lamp", at(4)), Some(15.0));
+        assert_eq!(inventory.price_at("Lamp", at(5)), Some(20.0));
+        assert_eq!(inventory.price_at("Lamp", at(11)), Some(25.0));
+
+        inventory.clock = Box::new(FixedClock(at(4)));
+        assert_eq!(inventory.record_sale_at_current_price("Lamp", 2), Ok(15.0));
+        assert_eq!(inventory.sales[0].sale_price, 15.0);
+
+        inventory.clock = Box::new(FixedClock(at(12)));
+        assert_eq!(inventory.apply_due_prices(), 1);
+        assert_eq!(inventory.products[0].price, 25.0);
+    }
+}
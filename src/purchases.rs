@@ -1,4 +1,7 @@
+use crate::clock::DateRange;
 use crate::inventory::{Product, Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 
 
 #[allow(dead_code)]
@@ -9,14 +12,26 @@ pub trait PurchaseManagement {
         quantity_purchased: u32,
         purchase_price: f64,
     ) -> Result<(), InventoryError>;
+    fn record_purchase_from(
+        &mut self,
+        supplier: &str,
+        product_name: &str,
+        quantity_purchased: u32,
+        purchase_price: f64,
+    ) -> Result<(), InventoryError>;
     fn total_purchase_cost(&self) -> f64;
+    fn total_purchase_cost_between(&self, range: &DateRange) -> f64;
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Purchase {
+    pub id: u32,
+    pub supplier: String,
     pub product_name: String,
     pub quantity_purchased: u32,
     pub purchase_price: f64,
     pub total_cost: f64,
+    pub timestamp: NaiveDateTime,
 }
 
 impl PurchaseManagement for Inventory {
@@ -25,6 +40,16 @@ impl PurchaseManagement for Inventory {
         product_name: &str,
         quantity_purchased: u32,
         purchase_price: f64,
+    ) -> Result<(), InventoryError> {
+        self.record_purchase_from("Unknown supplier", product_name, quantity_purchased, purchase_price)
+    }
+
+    fn record_purchase_from(
+        &mut self,
+        supplier: &str,
+        product_name: &str,
+        quantity_purchased: u32,
+        purchase_price: f64,
     ) -> Result<(), InventoryError> {
         if quantity_purchased == 0 || purchase_price <= 0.0 {
             return Err(InventoryError::InvalidInput(
@@ -33,33 +58,42 @@ impl PurchaseManagement for Inventory {
         }
 
         let total_cost = purchase_price * quantity_purchased as f64;
+        let id = self.purchases.len() as u32 + 1;
+        let timestamp = self.clock.now();
         let product = self.products.iter_mut().find(|p| p.name == product_name);
 
         match product {
             Some(p) => {
                 p.quantity += quantity_purchased;
                 let purchase = Purchase {
+                    id,
+                    supplier: supplier.to_string(),
                     product_name: product_name.to_string(),
                     quantity_purchased,
                     purchase_price,
                     total_cost,
+                    timestamp,
                 };
                 self.purchases.push(purchase);
                 Ok(())
             }
             None => {
-                let new_product = Product {
-                    name: product_name.to_string(),
-                    description: "Newly purchased product".to_string(),
-                    price: purchase_price,
-                    quantity: quantity_purchased,
-                };
+                let mut new_product = Product::new(
+                    product_name.to_string(),
+                    "Newly purchased product".to_string(),
+                    purchase_price,
+                    quantity_purchased,
+                );
+                new_product.created = Some(timestamp);
                 self.products.push(new_product);
                 let purchase = Purchase {
+                    id,
+                    supplier: supplier.to_string(),
                     product_name: product_name.to_string(),
                     quantity_purchased,
                     purchase_price,
                     total_cost,
+                    timestamp,
                 };
                 self.purchases.push(purchase);
                 Ok(())
@@ -68,10 +102,22 @@ impl PurchaseManagement for Inventory {
     }
 
     fn total_purchase_cost(&self) -> f64 {
+        self.total_purchase_cost_between(&DateRange::all())
+    }
+
+    fn total_purchase_cost_between(&self, range: &DateRange) -> f64 {
+        let credited: f64 = self
+            .supplier_returns
+            .iter()
+            .filter(|r| range.contains(&r.timestamp))
+            .map(|r| r.credit_amount)
+            .sum();
         self.purchases
             .iter()
+            .filter(|purchase| range.contains(&purchase.timestamp))
             .map(|purchase| purchase.total_cost)
-            .sum()
+            .sum::<f64>()
+            - credited
     }
 
 }
@@ -80,7 +126,9 @@ impl PurchaseManagement for Inventory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::FixedClock;
     use crate::inventory::{Inventory, InventoryError, InventoryManagement};
+    use chrono::NaiveDate;
 
     #[test]
     fn test_record_purchase_success_existing_product() {
@@ -151,6 +199,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_cast)] // the casts spell out the expected arithmetic
     fn test_total_purchase_cost() {
         let mut inventory = Inventory::new();
         inventory.record_purchase("Monitor", 2, 200.0).unwrap();
@@ -159,4 +208,17 @@ mod tests {
         let total_cost = inventory.total_purchase_cost();
         assert_eq!(total_cost, (2 as f64) * 200.0 + (3 as f64) * 50.0); // 400.0 + 150.0
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_total_purchase_cost_between() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(day(3).and_hms_opt(8, 0, 0).unwrap())));
+        inventory.record_purchase("Monitor", 2, 200.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(20).and_hms_opt(8, 0, 0).unwrap()));
+        inventory.record_purchase("Keyboard", 3, 50.0).unwrap();
+
+        assert_eq!(inventory.total_purchase_cost_between(&DateRange::new(day(1), day(10))), 400.0);
+        assert_eq!(inventory.total_purchase_cost_between(&DateRange::new(day(11), day(31))), 150.0);
+        assert_eq!(inventory.total_purchase_cost_between(&DateRange::new(day(4), day(19))), 0.0);
+    }
+}
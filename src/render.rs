@@ -0,0 +1,511 @@
+use crate::reporting::{Report, ReportTable, Value};
+use serde_json::json;
+
+/// Turns a `Report` into text for one destination. The caller decides whether
+/// the output goes to the terminal, a file or another program.
+pub trait ReportRenderer {
+    fn render(&self, report: &Report) -> String;
+}
+
+/// Fixed-width columns for reading in the terminal.
+pub struct TextRenderer;
+
+impl TextRenderer {
+    fn render_table(&self, table: &ReportTable, out: &mut String) {
+        if table.rows.is_empty() {
+            out.push_str(&table.empty_message);
+            out.push('\n');
+            return;
+        }
+
+        let cells = |row: &[Value]| -> Vec<String> { row.iter().map(|v| v.to_string()).collect() };
+        let rows: Vec<Vec<String>> = table.rows.iter().map(|row| cells(row)).collect();
+        let totals = table.totals.as_ref().map(|row| cells(row));
+
+        let mut widths: Vec<usize> = table.columns.iter().map(|c| c.chars().count()).collect();
+        for row in rows.iter().chain(totals.iter()) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |row: &[String]| -> String {
+            let padded: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            padded.join("  ").trim_end().to_string()
+        };
+        let rule = "-".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1));
+
+        out.push_str(&line(&table.columns));
+        out.push('\n');
+        out.push_str(&rule);
+        out.push('\n');
+        for row in &rows {
+            out.push_str(&line(row));
+            out.push('\n');
+        }
+        if let Some(totals) = totals {
+            out.push_str(&rule);
+            out.push('\n');
+            out.push_str(&line(&totals));
+            out.push('\n');
+        }
+    }
+}
+
+impl ReportRenderer for TextRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut out = format!("\n--- {} ---\n", report.title);
+        for (i, table) in report.tables.iter().enumerate() {
+            if report.tables.len() > 1 {
+                if i > 0 {
+                    out.push('\n');
+                }
+                out.push_str(&format!("{}\n", table.title));
+            }
+            self.render_table(table, &mut out);
+        }
+        if !report.summary.is_empty() {
+            out.push('\n');
+            for (label, value) in &report.summary {
+                out.push_str(&format!("{}: {}\n", label, value));
+            }
+        }
+        out
+    }
+}
+
+/// The file formats a report can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "html" | "htm" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    pub fn renderer(&self) -> Box<dyn ReportRenderer> {
+        match self {
+            ExportFormat::Csv => Box::new(CsvRenderer),
+            ExportFormat::Json => Box::new(JsonRenderer),
+            ExportFormat::Markdown => Box::new(MarkdownRenderer),
+            ExportFormat::Html => Box::new(HtmlRenderer),
+        }
+    }
+}
+
+// Machine-readable form of a value: no currency sign, full date and time.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Money(amount) => format!("{:.2}", amount),
+        Value::Date(timestamp) => timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// One block per table (title, header, rows, totals) followed by the summary
+/// as label/value pairs. Quoting follows RFC 4180.
+pub struct CsvRenderer;
+
+impl CsvRenderer {
+    fn block(records: &[Vec<String>]) -> String {
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+        for record in records {
+            writer.write_record(record).expect("writing CSV to memory cannot fail");
+        }
+        let bytes = writer.into_inner().expect("writing CSV to memory cannot fail");
+        String::from_utf8(bytes).expect("CSV output is built from UTF-8 strings")
+    }
+}
+
+impl ReportRenderer for CsvRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut blocks = vec![CsvRenderer::block(&[vec![report.title.clone()]])];
+        for table in &report.tables {
+            let mut records = vec![vec![table.title.clone()], table.columns.clone()];
+            records.extend(table.rows.iter().map(|row| row.iter().map(plain).collect()));
+            if let Some(totals) = &table.totals {
+                records.push(totals.iter().map(plain).collect());
+            }
+            blocks.push(CsvRenderer::block(&records));
+        }
+        if !report.summary.is_empty() {
+            let records: Vec<Vec<String>> = report
+                .summary
+                .iter()
+                .map(|(label, value)| vec![label.clone(), plain(value)])
+                .collect();
+            blocks.push(CsvRenderer::block(&records));
+        }
+        // Blank lines separate the blocks.
+        blocks.join("\n")
+    }
+}
+
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+    fn value(value: &Value) -> serde_json::Value {
+        match value {
+            Value::Text(text) => json!(text),
+            Value::Integer(number) => json!(number),
+            Value::Money(amount) => json!((amount * 100.0).round() / 100.0),
+            Value::Date(timestamp) => json!(timestamp.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            Value::Empty => serde_json::Value::Null,
+        }
+    }
+
+    fn row(columns: &[String], row: &[Value]) -> serde_json::Value {
+        let fields: serde_json::Map<String, serde_json::Value> = columns
+            .iter()
+            .zip(row)
+            .map(|(column, value)| (column.clone(), JsonRenderer::value(value)))
+            .collect();
+        serde_json::Value::Object(fields)
+    }
+}
+
+impl ReportRenderer for JsonRenderer {
+    fn render(&self, report: &Report) -> String {
+        let tables: Vec<serde_json::Value> = report
+            .tables
+            .iter()
+            .map(|table| {
+                json!({
+                    "title": table.title,
+                    "columns": table.columns,
+                    "rows": table.rows.iter().map(|row| JsonRenderer::row(&table.columns, row)).collect::<Vec<_>>(),
+                    "totals": table.totals.as_ref().map(|row| JsonRenderer::row(&table.columns, row)),
+                })
+            })
+            .collect();
+        let summary: serde_json::Map<String, serde_json::Value> = report
+            .summary
+            .iter()
+            .map(|(label, value)| (label.clone(), JsonRenderer::value(value)))
+            .collect();
+
+        let document = json!({
+            "title": report.title,
+            "tables": tables,
+            "summary": summary,
+        });
+        serde_json::to_string_pretty(&document).expect("report JSON always serializes") + "\n"
+    }
+}
+
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    // Escapes pipes and turns line breaks into <br> so a value stays inside its cell.
+    fn cell(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace(['\n', '\r'], "<br>")
+    }
+
+    fn row(cells: &[String]) -> String {
+        format!("| {} |\n", cells.join(" | "))
+    }
+}
+
+impl ReportRenderer for MarkdownRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut out = format!("# {}\n", MarkdownRenderer::cell(&report.title));
+        for table in &report.tables {
+            out.push_str(&format!("\n## {}\n\n", MarkdownRenderer::cell(&table.title)));
+            if table.rows.is_empty() {
+                out.push_str(&format!("{}\n", MarkdownRenderer::cell(&table.empty_message)));
+                continue;
+            }
+
+            let header: Vec<String> = table.columns.iter().map(|c| MarkdownRenderer::cell(c)).collect();
+            out.push_str(&MarkdownRenderer::row(&header));
+            out.push_str(&MarkdownRenderer::row(&vec!["---".to_string(); header.len()]));
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(|v| MarkdownRenderer::cell(&v.to_string())).collect();
+                out.push_str(&MarkdownRenderer::row(&cells));
+            }
+            if let Some(totals) = &table.totals {
+                let cells: Vec<String> = totals
+                    .iter()
+                    .map(|v| match v {
+                        Value::Empty => String::new(),
+                        v => format!("**{}**", MarkdownRenderer::cell(&v.to_string())),
+                    })
+                    .collect();
+                out.push_str(&MarkdownRenderer::row(&cells));
+            }
+        }
+        if !report.summary.is_empty() {
+            out.push('\n');
+            for (label, value) in &report.summary {
+                out.push_str(&format!(
+                    "- **{}:** {}\n",
+                    MarkdownRenderer::cell(label),
+                    MarkdownRenderer::cell(&value.to_string())
+                ));
+            }
+        }
+        out
+    }
+}
+
+const HTML_STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem; color: #222; }
+h1 { border-bottom: 2px solid #444; padding-bottom: 0.3rem; }
+section { margin-bottom: 2.5rem; }
+table { border-collapse: collapse; margin: 0.5rem 0 1rem; min-width: 40%; }
+th, td { border: 1px solid #ccc; padding: 0.35rem 0.7rem; text-align: left; white-space: pre-wrap; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; }
+th[data-dir='asc']::after { content: ' ▲'; }
+th[data-dir='desc']::after { content: ' ▼'; }
+tbody tr:nth-child(even) { background: #fafafa; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+tfoot td { font-weight: bold; background: #eef3f8; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.2rem 1rem; }
+dt { font-weight: bold; }
+";
+
+// Click a header to sort by that column; totals stay in <tfoot> and never move.
+const HTML_SCRIPT: &str = "
+document.querySelectorAll('table.report th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table');
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var dir = th.dataset.dir === 'asc' ? 'desc' : 'asc';
+    table.querySelectorAll('th').forEach(function (other) { delete other.dataset.dir; });
+    th.dataset.dir = dir;
+    var key = function (row) {
+      var cell = row.children[index];
+      var sort = cell.dataset.sort;
+      return sort !== undefined && sort !== '' && !isNaN(sort) ? parseFloat(sort) : cell.textContent.toLowerCase();
+    };
+    Array.prototype.slice.call(body.rows)
+      .sort(function (a, b) {
+        var x = key(a), y = key(b);
+        var order = x < y ? -1 : x > y ? 1 : 0;
+        return dir === 'asc' ? order : -order;
+      })
+      .forEach(function (row) { body.appendChild(row); });
+  });
+});
+";
+
+/// A single HTML page with inline styles and script, so it opens offline in
+/// any browser. Several reports can share one page as separate sections.
+pub struct HtmlRenderer;
+
+impl HtmlRenderer {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#39;")
+    }
+
+    fn cell(value: &Value) -> String {
+        let text = HtmlRenderer::escape(&value.to_string());
+        match value {
+            Value::Integer(number) => format!("<td class=\"num\" data-sort=\"{}\">{}</td>", number, text),
+            Value::Money(amount) => format!("<td class=\"num\" data-sort=\"{:.2}\">{}</td>", amount, text),
+            Value::Date(timestamp) => {
+                format!("<td data-sort=\"{}\">{}</td>", timestamp.format("%Y%m%d%H%M%S"), text)
+            }
+            _ => format!("<td>{}</td>", text),
+        }
+    }
+
+    fn section(report: &Report, out: &mut String) {
+        out.push_str(&format!("<section>\n<h2>{}</h2>\n", HtmlRenderer::escape(&report.title)));
+        for table in &report.tables {
+            if report.tables.len() > 1 {
+                out.push_str(&format!("<h3>{}</h3>\n", HtmlRenderer::escape(&table.title)));
+            }
+            if table.rows.is_empty() {
+                out.push_str(&format!("<p>{}</p>\n", HtmlRenderer::escape(&table.empty_message)));
+                continue;
+            }
+
+            out.push_str("<table class=\"report\">\n<thead><tr>");
+            for column in &table.columns {
+                out.push_str(&format!("<th>{}</th>", HtmlRenderer::escape(column)));
+            }
+            out.push_str("</tr></thead>\n<tbody>\n");
+            for row in &table.rows {
+                out.push_str("<tr>");
+                for value in row {
+                    out.push_str(&HtmlRenderer::cell(value));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</tbody>\n");
+            if let Some(totals) = &table.totals {
+                out.push_str("<tfoot><tr>");
+                for value in totals {
+                    out.push_str(&HtmlRenderer::cell(value));
+                }
+                out.push_str("</tr></tfoot>\n");
+            }
+            out.push_str("</table>\n");
+        }
+        if !report.summary.is_empty() {
+            out.push_str("<dl>\n");
+            for (label, value) in &report.summary {
+                out.push_str(&format!(
+                    "<dt>{}</dt><dd>{}</dd>\n",
+                    HtmlRenderer::escape(label),
+                    HtmlRenderer::escape(&value.to_string())
+                ));
+            }
+            out.push_str("</dl>\n");
+        }
+        out.push_str("</section>\n");
+    }
+
+    pub fn render_document(&self, title: &str, reports: &[Report]) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            HtmlRenderer::escape(title),
+            HTML_STYLE,
+            HtmlRenderer::escape(title)
+        );
+        for report in reports {
+            HtmlRenderer::section(report, &mut out);
+        }
+        out.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", HTML_SCRIPT));
+        out
+    }
+}
+
+impl ReportRenderer for HtmlRenderer {
+    fn render(&self, report: &Report) -> String {
+        self.render_document(&report.title, std::slice::from_ref(report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_renderer_aligns_columns() {
+        let mut table = ReportTable::new("Products", &["Name", "Qty"], "No products.");
+        table.rows.push(vec![Value::Text("Laptop".to_string()), Value::Integer(10)]);
+        table.totals = Some(vec![Value::Text("Total".to_string()), Value::Integer(10)]);
+        let mut report = Report::new("Inventory Report");
+        report.tables.push(table);
+        report.summary.push(("Value".to_string(), Value::Money(5.0)));
+
+        let text = TextRenderer.render(&report);
+        assert_eq!(
+            text,
+            "\n--- Inventory Report ---\nName    Qty\n-----------\nLaptop  10\n-----------\nTotal   10\n\nValue: $5.00\n"
+        );
+    }
+
+    #[test]
+    fn test_text_renderer_empty_table() {
+        let mut report = Report::new("Sales Report");
+        report.tables.push(ReportTable::new("Sales", &["ID"], "No sales recorded."));
+
+        assert_eq!(TextRenderer.render(&report), "\n--- Sales Report ---\nNo sales recorded.\n");
+    }
+
+    fn tricky_report() -> Report {
+        let mut table = ReportTable::new("Products", &["Name", "Price", "Description"], "No products.");
+        table.rows.push(vec![
+            Value::Text("Desk, \"oak\"".to_string()),
+            Value::Money(99.5),
+            Value::Text("Line one\nline | two".to_string()),
+        ]);
+        let mut report = Report::new("Inventory Report");
+        report.tables.push(table);
+        report.summary.push(("Total".to_string(), Value::Money(99.5)));
+        report
+    }
+
+    #[test]
+    fn test_csv_renderer_escapes_fields() {
+        let csv = CsvRenderer.render(&tricky_report());
+        assert_eq!(
+            csv,
+            "Inventory Report\n\nProducts\nName,Price,Description\n\"Desk, \"\"oak\"\"\",99.50,\"Line one\nline | two\"\n\nTotal,99.50\n"
+        );
+    }
+
+    #[test]
+    fn test_json_renderer_keeps_types() {
+        let json: serde_json::Value = serde_json::from_str(&JsonRenderer.render(&tricky_report())).unwrap();
+        let row = &json["tables"][0]["rows"][0];
+        assert_eq!(row["Name"], "Desk, \"oak\"");
+        assert_eq!(row["Price"], 99.5);
+        assert_eq!(row["Description"], "Line one\nline | two");
+        assert_eq!(json["summary"]["Total"], 99.5);
+    }
+
+    #[test]
+    fn test_markdown_renderer_escapes_cells() {
+        let markdown = MarkdownRenderer.render(&tricky_report());
+        assert!(markdown.starts_with("# Inventory Report\n\n## Products\n\n| Name | Price | Description |\n| --- | --- | --- |\n"));
+        assert!(markdown.contains("| Desk, \"oak\" | $99.50 | Line one<br>line \\| two |\n"));
+        assert!(markdown.ends_with("- **Total:** $99.50\n"));
+    }
+
+    #[test]
+    fn test_export_format_from_name() {
+        assert_eq!(ExportFormat::from_name("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_name("markdown"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_name("xlsx"), None);
+    }
+
+    #[test]
+    fn test_html_renderer_is_self_contained() {
+        let html = HtmlRenderer.render(&tricky_report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("<script>"));
+        assert!(!html.contains("href=") && !html.contains("src="));
+        assert!(html.contains("<td>Desk, &quot;oak&quot;</td><td class=\"num\" data-sort=\"99.50\">$99.50</td>"));
+    }
+
+    #[test]
+    fn test_html_renderer_groups_reports() {
+        let mut sales = Report::new("Sales Report");
+        let mut table = ReportTable::new("Sales", &["ID", "Total"], "No sales recorded.");
+        table.rows.push(vec![Value::Integer(1), Value::Money(10.0)]);
+        table.totals = Some(vec![Value::Text("Total".to_string()), Value::Money(10.0)]);
+        sales.tables.push(table);
+
+        let html = HtmlRenderer.render_document("Weekly Review", &[tricky_report(), sales]);
+        assert_eq!(html.matches("<section>").count(), 2);
+        assert!(html.contains("<h2>Sales Report</h2>"));
+        assert!(html.contains("<tfoot><tr><td>Total</td><td class=\"num\" data-sort=\"10.00\">$10.00</td></tr></tfoot>"));
+    }
+}
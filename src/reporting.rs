@@ -1,74 +1,954 @@
-use crate::inventory::Inventory;
+use crate::abc::AbcClass;
+use crate::adjustments::AdjustmentReason;
+use crate::clock::DateRange;
+use crate::forecast::{ForecastSettings, Forecasting};
+use crate::inventory::{Inventory, InventoryError, Product};
+use crate::pricing::PriceManagement;
+use crate::purchases::PurchaseManagement;
+use crate::sales::SalesManagement;
+use crate::valuation::Valuation;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A single typed cell of a report. Renderers decide how each kind is shown.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Money(f64),
+    Date(NaiveDateTime),
+    Empty,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Integer(number) => write!(f, "{}", number),
+            Value::Money(amount) if *amount < 0.0 => write!(f, "-${:.2}", -amount),
+            Value::Money(amount) => write!(f, "${:.2}", amount),
+            Value::Date(timestamp) => write!(f, "{}", timestamp.format("%Y-%m-%d %H:%M")),
+            Value::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(number: u32) -> Self {
+        Value::Integer(number as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(number: i64) -> Self {
+        Value::Integer(number)
+    }
+}
+
+impl From<NaiveDateTime> for Value {
+    fn from(timestamp: NaiveDateTime) -> Self {
+        Value::Date(timestamp)
+    }
+}
+
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub totals: Option<Vec<Value>>,
+    pub empty_message: String,
+}
+
+impl ReportTable {
+    pub fn new(title: &str, columns: &[&str], empty_message: &str) -> Self {
+        ReportTable {
+            title: title.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+            totals: None,
+            empty_message: empty_message.to_string(),
+        }
+    }
+}
+
+/// A finished report: one or more tables plus labelled summary figures.
+pub struct Report {
+    pub title: String,
+    pub tables: Vec<ReportTable>,
+    pub summary: Vec<(String, Value)>,
+}
+
+impl Report {
+    pub fn new(title: &str) -> Self {
+        Report {
+            title: title.to_string(),
+            tables: Vec::new(),
+            summary: Vec::new(),
+        }
+    }
+}
+
+/// How rows of a breakdown report are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Product,
+    Category,
+}
+
+#[derive(Default)]
+struct ProfitAndLossLine {
+    revenue: f64,
+    returns: f64,
+    cost_of_goods_sold: f64,
+    adjustments: f64,
+}
+
+impl ProfitAndLossLine {
+    fn gross_margin(&self) -> f64 {
+        self.revenue - self.returns - self.cost_of_goods_sold
+    }
+
+    fn row(&self, label: &str) -> Vec<Value> {
+        let net_revenue = self.revenue - self.returns;
+        let margin_percent = if net_revenue != 0.0 {
+            Value::Text(format!("{:.1}%", self.gross_margin() / net_revenue * 100.0))
+        } else {
+            Value::Empty
+        };
+        vec![
+            label.into(),
+            Value::Money(self.revenue),
+            Value::Money(-self.returns),
+            Value::Money(net_revenue),
+            Value::Money(self.cost_of_goods_sold),
+            Value::Money(self.gross_margin()),
+            margin_percent,
+            Value::Money(self.adjustments),
+            Value::Money(self.gross_margin() + self.adjustments),
+        ]
+    }
+}
+
+/// Net sales of one product over a period, after customer returns.
+#[derive(Default)]
+pub(crate) struct SalesLine {
+    pub(crate) units: i64,
+    pub(crate) revenue: f64,
+    pub(crate) profit: f64,
+}
+
+/// The `limit` products with the highest `key`, best first.
+fn top_sellers_table(
+    title: &str,
+    lines: &BTreeMap<String, SalesLine>,
+    limit: usize,
+    key: impl Fn(&SalesLine) -> f64,
+) -> ReportTable {
+    let mut ranked: Vec<(&String, &SalesLine)> = lines.iter().collect();
+    ranked.sort_by(|a, b| key(b.1).total_cmp(&key(a.1)));
+    let mut table = ReportTable::new(
+        title,
+        &["Rank", "Product Name", "Units Sold", "Revenue", "Profit"],
+        "No sales in this period.",
+    );
+    for (rank, (name, line)) in ranked.into_iter().take(limit).enumerate() {
+        table.rows.push(vec![
+            Value::Integer(rank as i64 + 1),
+            name.as_str().into(),
+            line.units.into(),
+            Value::Money(line.revenue),
+            Value::Money(line.profit),
+        ]);
+    }
+    table
+}
+
+impl Report {
+    /// Notes the ABC class a product report was limited to, if any.
+    fn note_class(&mut self, class: Option<AbcClass>) {
+        if let Some(class) = class {
+            self.summary.push(("ABC class".to_string(), class.to_string().into()));
+        }
+    }
+}
+
+impl Inventory {
+    pub(crate) fn sales_by_product(&self, range: &DateRange) -> BTreeMap<String, SalesLine> {
+        let mut lines: BTreeMap<String, SalesLine> = BTreeMap::new();
+        for sale in self.sales.iter().filter(|sale| range.contains(&sale.timestamp)) {
+            let line = lines.entry(sale.product_name.clone()).or_default();
+            line.units += sale.quantity_sold as i64;
+            line.revenue += sale.total_sale;
+            line.profit += sale.profit;
+        }
+        for sale_return in self.returns.iter().filter(|r| range.contains(&r.timestamp)) {
+            let line = lines.entry(sale_return.product_name.clone()).or_default();
+            line.units -= sale_return.quantity_returned() as i64;
+            line.revenue -= sale_return.refund_amount;
+            line.profit -= sale_return.profit_reversed;
+        }
+        lines
+    }
+
+    /// The name of the group a product belongs to. Products that have since been
+    /// deleted keep their name but fall under "Uncategorized".
+    fn group_label(&self, product_name: &str, grouping: Grouping) -> String {
+        match grouping {
+            Grouping::Product => product_name.to_string(),
+            Grouping::Category => self
+                .products
+                .iter()
+                .find(|p| p.name == product_name)
+                .map(|p| p.category.clone())
+                .filter(|c| !c.is_empty())
+                .unwrap_or_else(|| "Uncategorized".to_string()),
+        }
+    }
+}
 
 pub trait Reporting {
-    fn generate_inventory_report(&self);
+    fn generate_inventory_report(&self, class: Option<AbcClass>) -> Report;
+
+    fn generate_sales_report(&self, range: &DateRange) -> Report;
+
+    fn generate_purchase_report(&self, range: &DateRange) -> Report;
+
+    fn generate_adjustment_report(&self) -> Report;
 
-    fn generate_sales_report(&self);
+    fn generate_stock_take_report(&self, stock_take_id: u32) -> Result<Report, InventoryError>;
 
-    fn generate_purchase_report(&self);
+    fn generate_profit_and_loss_report(&self, range: &DateRange, grouping: Grouping) -> Report;
+
+    fn generate_valuation_report(&self, as_of: NaiveDate, class: Option<AbcClass>) -> Report;
+
+    fn generate_top_sellers_report(&self, range: &DateRange, limit: usize) -> Report;
+
+    fn generate_slow_movers_report(&self, range: &DateRange, threshold_percent: f64, class: Option<AbcClass>) -> Report;
+
+    fn generate_dead_stock_report(&self, days: u32, class: Option<AbcClass>) -> Report;
+
+    fn generate_forecast_report(&self, settings: &ForecastSettings) -> Result<Report, InventoryError>;
+
+    fn generate_price_history_report(&self, product_name: Option<&str>) -> Report;
 }
 
 impl Reporting for Inventory {
-    fn generate_inventory_report(&self) {
-        println!("\n--- Inventory Report ---");
+    fn generate_inventory_report(&self, class: Option<AbcClass>) -> Report {
+        let mut table = ReportTable::new(
+            "Products",
+            &["Product Name", "Quantity", "Price", "Description", "Class"],
+            "No products in inventory.",
+        );
+        for product in self.products.iter().filter(|p| !p.archived && p.is_in_class(class)) {
+            table.rows.push(vec![
+                product.name.as_str().into(),
+                product.quantity.into(),
+                Value::Money(product.price),
+                product.description.as_str().into(),
+                product.abc_class.map_or(Value::Empty, |c| c.to_string().into()),
+            ]);
+        }
 
-        if self.products.is_empty() {
-            println!("No products in inventory.");
-        } else {
-            println!(
-                "{:<20} {:<10} {:<10} {:<10}",
-                "Product Name", "Quantity", "Price", "Description"
-            );
-            println!("{:-<60}", "");
-
-            for product in &self.products {
-                println!(
-                    "{:<20} {:<10} ${:<9.2} {:<10}",
-                    product.name, product.quantity, product.price, product.description
-                );
+        let mut report = Report::new("Inventory Report");
+        report.tables.push(table);
+        report.note_class(class);
+        report
+    }
+
+    fn generate_sales_report(&self, range: &DateRange) -> Report {
+        let mut table = ReportTable::new(
+            "Sales",
+            &["ID", "Date", "Product Name", "Quantity", "Sale Price", "Total Sale", "Profit", "Receipt"],
+            "No sales recorded.",
+        );
+        for sale in self.sales.iter().filter(|sale| range.contains(&sale.timestamp)) {
+            table.rows.push(vec![
+                sale.id.into(),
+                sale.timestamp.into(),
+                sale.product_name.as_str().into(),
+                sale.quantity_sold.into(),
+                Value::Money(sale.sale_price),
+                Value::Money(sale.total_sale),
+                Value::Money(sale.profit),
+                sale.receipt_id.into(),
+            ]);
+        }
+        // Returns are listed against the sale they reverse, as negative lines.
+        for sale_return in self.returns.iter().filter(|r| range.contains(&r.timestamp)) {
+            table.rows.push(vec![
+                format!("R{}", sale_return.sale_id).into(),
+                sale_return.timestamp.into(),
+                sale_return.product_name.as_str().into(),
+                (-(sale_return.quantity_returned() as i64)).into(),
+                Value::Empty,
+                Value::Money(-sale_return.refund_amount),
+                Value::Money(-sale_return.profit_reversed),
+                Value::Empty,
+            ]);
+        }
+        let net_sales = self.total_sales_between(range);
+        let net_profit = self.total_profit_between(range);
+        table.totals = Some(vec![
+            "Total".into(),
+            Value::Empty,
+            Value::Empty,
+            Value::Empty,
+            Value::Empty,
+            Value::Money(net_sales),
+            Value::Money(net_profit),
+            Value::Empty,
+        ]);
+
+        let mut report = Report::new(&format!("Sales Report ({})", range));
+        report.tables.push(table);
+        report.summary.push(("Net sales".to_string(), Value::Money(net_sales)));
+        report.summary.push(("Net profit".to_string(), Value::Money(net_profit)));
+        report
+    }
+
+    fn generate_purchase_report(&self, range: &DateRange) -> Report {
+        let mut purchases = ReportTable::new(
+            "Purchases",
+            &["ID", "Date", "Supplier", "Product Name", "Quantity", "Purchase Price", "Total Cost"],
+            "No purchases recorded.",
+        );
+        for purchase in self.purchases.iter().filter(|purchase| range.contains(&purchase.timestamp)) {
+            purchases.rows.push(vec![
+                purchase.id.into(),
+                purchase.timestamp.into(),
+                purchase.supplier.as_str().into(),
+                purchase.product_name.as_str().into(),
+                purchase.quantity_purchased.into(),
+                Value::Money(purchase.purchase_price),
+                Value::Money(purchase.total_cost),
+            ]);
+        }
+
+        let mut credit_notes = ReportTable::new(
+            "Supplier Returns & Credit Notes",
+            &["Note", "Purchase", "Supplier", "Product Name", "Quantity", "Credit", "Status", "Reason"],
+            "No supplier returns recorded.",
+        );
+        for supplier_return in self.supplier_returns.iter().filter(|r| range.contains(&r.timestamp)) {
+            credit_notes.rows.push(vec![
+                supplier_return.id.into(),
+                supplier_return.purchase_id.into(),
+                supplier_return.supplier.as_str().into(),
+                supplier_return.product_name.as_str().into(),
+                supplier_return.quantity_returned.into(),
+                Value::Money(supplier_return.credit_amount),
+                if supplier_return.credit_settled { "Settled" } else { "Outstanding" }.into(),
+                supplier_return.reason.as_str().into(),
+            ]);
+        }
+
+        let mut report = Report::new(&format!("Purchase Report ({})", range));
+        report.tables.push(purchases);
+        if !credit_notes.rows.is_empty() {
+            report.tables.push(credit_notes);
+        }
+        report.summary.push((
+            "Net purchase cost".to_string(),
+            Value::Money(self.total_purchase_cost_between(range)),
+        ));
+        report
+    }
+
+    fn generate_adjustment_report(&self) -> Report {
+        let mut adjustments = ReportTable::new(
+            "Adjustments",
+            &["Date", "Product Name", "Change", "Reason", "User", "Value"],
+            "No stock adjustments recorded.",
+        );
+        for adjustment in &self.adjustments {
+            adjustments.rows.push(vec![
+                adjustment.timestamp.into(),
+                adjustment.product_name.as_str().into(),
+                adjustment.quantity_change.into(),
+                adjustment.reason.to_string().into(),
+                adjustment.user.as_str().into(),
+                Value::Money(adjustment.value()),
+            ]);
+        }
+
+        // Shrinkage is the value lost, so stock that turned up counts against it.
+        let mut shrinkage: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for adjustment in &self.adjustments {
+            let period = adjustment.timestamp.format("%Y-%m").to_string();
+            let by_reason = shrinkage.entry(period).or_insert_with(|| vec![0.0; AdjustmentReason::ALL.len()]);
+            let index = AdjustmentReason::ALL.iter().position(|r| *r == adjustment.reason).unwrap();
+            by_reason[index] -= adjustment.value();
+        }
+
+        let mut by_period = ReportTable::new(
+            "Shrinkage by Reason and Period",
+            &["Period", "Reason", "Shrinkage"],
+            "No shrinkage recorded.",
+        );
+        for (period, by_reason) in &shrinkage {
+            for (reason, value) in AdjustmentReason::ALL.iter().zip(by_reason) {
+                if *value != 0.0 {
+                    by_period
+                        .rows
+                        .push(vec![period.as_str().into(), reason.to_string().into(), Value::Money(*value)]);
+                }
             }
+            by_period.rows.push(vec![
+                period.as_str().into(),
+                "Total".into(),
+                Value::Money(by_reason.iter().sum()),
+            ]);
         }
+
+        let mut report = Report::new("Stock Adjustment Report");
+        report.tables.push(adjustments);
+        report.tables.push(by_period);
+        report
     }
 
-    fn generate_sales_report(&self) {
-        println!("\n--- Sales Report ---");
-        if self.sales.is_empty() {
-            println!("No sales recorded.");
-        } else {
-            println!(
-                "{:<20} {:<10} {:<10} {:<10} {:<10}",
-                "Product Name", "Quantity", "Sale Price", "Total Sale", "Profit"
-            );
-            println!("{:-<70}", "");
-            for sale in &self.sales {
-                let total_sale = sale.sale_price * sale.quantity_sold as f64;
-                println!(
-                    "{:<20} {:<10} ${:<9.2} ${:<9.2} ${:<9.2}",
-                    sale.product_name, sale.quantity_sold, sale.sale_price, total_sale, sale.profit
-                );
+    fn generate_stock_take_report(&self, stock_take_id: u32) -> Result<Report, InventoryError> {
+        let stock_take = match self.stock_takes.iter().find(|t| t.id == stock_take_id) {
+            Some(stock_take) => stock_take,
+            None => {
+                return Err(InventoryError::StockTakeFailed(format!(
+                    "Stock take #{} not found",
+                    stock_take_id
+                )))
             }
+        };
+
+        let mut table = ReportTable::new(
+            "Variances",
+            &["Product Name", "Expected", "Counted", "Variance", "Value"],
+            "No products in this stock take.",
+        );
+        for line in &stock_take.lines {
+            table.rows.push(vec![
+                line.product_name.as_str().into(),
+                line.expected_quantity.into(),
+                line.counted_quantity.map_or(Value::Empty, Value::from),
+                line.variance().map_or(Value::Empty, Value::from),
+                Value::Money(line.variance_value()),
+            ]);
         }
+        let total: f64 = stock_take.lines.iter().map(|l| l.variance_value()).sum();
+        table.totals = Some(vec!["Total".into(), Value::Empty, Value::Empty, Value::Empty, Value::Money(total)]);
+
+        let mut report = Report::new(&format!("Stock Take #{} Variances", stock_take_id));
+        report.tables.push(table);
+        report.summary.push(("Started".to_string(), stock_take.started_at.into()));
+        report
+            .summary
+            .push(("Status".to_string(), if stock_take.posted { "Posted" } else { "Open" }.into()));
+        Ok(report)
     }
 
-    fn generate_purchase_report(&self) {
-        println!("\n--- Purchase Report ---");
-        if self.purchases.is_empty() {
-            println!("No purchases recorded.");
-        } else {
-            println!(
-                "{:<20} {:<10} {:<15} {:<10}",
-                "Product Name", "Quantity", "Purchase Price", "Total Cost"
-            );
-            println!("{:-<60}", "");
-            for purchase in &self.purchases {
-                println!(
-                    "{:<20} {:<10} ${:<14.2} ${:<9.2}",
-                    purchase.product_name,
-                    purchase.quantity_purchased,
-                    purchase.purchase_price,
-                    purchase.total_cost
-                );
+    fn generate_profit_and_loss_report(&self, range: &DateRange, grouping: Grouping) -> Report {
+        let mut lines: BTreeMap<String, ProfitAndLossLine> = BTreeMap::new();
+        for sale in self.sales.iter().filter(|sale| range.contains(&sale.timestamp)) {
+            let line = lines.entry(self.group_label(&sale.product_name, grouping)).or_default();
+            line.revenue += sale.total_sale;
+            line.cost_of_goods_sold += sale.total_sale - sale.profit;
+        }
+        // Restocked returns hand their cost back; written-off units stay in cost of goods sold.
+        for sale_return in self.returns.iter().filter(|r| range.contains(&r.timestamp)) {
+            let line = lines.entry(self.group_label(&sale_return.product_name, grouping)).or_default();
+            line.returns += sale_return.refund_amount;
+            if let Some(sale) = self.sales.iter().find(|s| s.id == sale_return.sale_id) {
+                let unit_cost = (sale.total_sale - sale.profit) / sale.quantity_sold as f64;
+                line.cost_of_goods_sold -= unit_cost * sale_return.quantity_restocked as f64;
+            }
+        }
+        for adjustment in self.adjustments.iter().filter(|a| range.contains(&a.timestamp)) {
+            let line = lines.entry(self.group_label(&adjustment.product_name, grouping)).or_default();
+            line.adjustments += adjustment.value();
+        }
+
+        let label = match grouping {
+            Grouping::Product => "Product Name",
+            Grouping::Category => "Category",
+        };
+        let mut table = ReportTable::new(
+            "Profit and Loss",
+            &[
+                label,
+                "Revenue",
+                "Returns",
+                "Net Revenue",
+                "Cost of Goods Sold",
+                "Gross Margin",
+                "Margin %",
+                "Adjustments",
+                "Net Result",
+            ],
+            "No activity in this period.",
+        );
+        let mut total = ProfitAndLossLine::default();
+        for (name, line) in &lines {
+            table.rows.push(line.row(name));
+            total.revenue += line.revenue;
+            total.returns += line.returns;
+            total.cost_of_goods_sold += line.cost_of_goods_sold;
+            total.adjustments += line.adjustments;
+        }
+        table.totals = Some(total.row("Total"));
+
+        let mut report = Report::new(&format!("Profit and Loss ({})", range));
+        report.tables.push(table);
+        report.summary.push(("Net revenue".to_string(), Value::Money(total.revenue - total.returns)));
+        report.summary.push(("Gross margin".to_string(), Value::Money(total.gross_margin())));
+        report.summary.push(("Adjustments and write-offs".to_string(), Value::Money(total.adjustments)));
+        report.summary.push((
+            "Net result".to_string(),
+            Value::Money(total.gross_margin() + total.adjustments),
+        ));
+        report
+    }
+
+    fn generate_valuation_report(&self, as_of: NaiveDate, class: Option<AbcClass>) -> Report {
+        let mut table = ReportTable::new(
+            "Stock on Hand",
+            &["Product Name", "Quantity", "Unit Cost", "Value at Cost", "Unit Price", "Value at Retail"],
+            "No stock on hand at this date.",
+        );
+        let mut total_quantity = 0u32;
+        let mut total_cost = 0.0;
+        let mut total_retail = 0.0;
+        let end_of_day = as_of.and_hms_opt(23, 59, 59).unwrap();
+        let existed = |p: &&Product| self.existed_on(&p.name, as_of);
+        for product in self.products.iter().filter(|p| p.is_in_class(class)).filter(existed) {
+            let quantity = self.quantity_at(&product.name, as_of);
+            if quantity == 0 {
+                continue;
+            }
+            let unit_cost = self.unit_cost_at(&product.name, as_of);
+            let cost_value = unit_cost * quantity as f64;
+            let unit_price = self.price_at(&product.name, end_of_day).unwrap_or(product.price);
+            let retail_value = unit_price * quantity as f64;
+            table.rows.push(vec![
+                product.name.as_str().into(),
+                quantity.into(),
+                Value::Money(unit_cost),
+                Value::Money(cost_value),
+                Value::Money(unit_price),
+                Value::Money(retail_value),
+            ]);
+            total_quantity += quantity;
+            total_cost += cost_value;
+            total_retail += retail_value;
+        }
+        table.totals = Some(vec![
+            "Total".into(),
+            total_quantity.into(),
+            Value::Empty,
+            Value::Money(total_cost),
+            Value::Empty,
+            Value::Money(total_retail),
+        ]);
+
+        let mut report = Report::new(&format!("Inventory Valuation as of {}", as_of));
+        report.tables.push(table);
+        report.summary.push(("Value at cost".to_string(), Value::Money(total_cost)));
+        report.summary.push(("Value at retail".to_string(), Value::Money(total_retail)));
+        report.note_class(class);
+        report
+    }
+
+    fn generate_top_sellers_report(&self, range: &DateRange, limit: usize) -> Report {
+        let lines = self.sales_by_product(range);
+        let mut report = Report::new(&format!("Top {} Sellers ({})", limit, range));
+        report.tables.push(top_sellers_table("By Units", &lines, limit, |line| line.units as f64));
+        report.tables.push(top_sellers_table("By Revenue", &lines, limit, |line| line.revenue));
+        report.tables.push(top_sellers_table("By Profit", &lines, limit, |line| line.profit));
+        report
+    }
+
+    // Sell-through is units sold as a share of the units that were available:
+    // those sold plus those still on hand at the end of the period.
+    fn generate_slow_movers_report(&self, range: &DateRange, threshold_percent: f64, class: Option<AbcClass>) -> Report {
+        let lines = self.sales_by_product(range);
+        let mut slow_movers = Vec::new();
+        for product in self.products.iter().filter(|p| p.is_in_class(class)) {
+            let sold = lines.get(&product.name).map_or(0, |line| line.units.max(0));
+            let on_hand = self.quantity_at(&product.name, range.to) as i64;
+            if sold + on_hand == 0 {
+                continue;
+            }
+            let sell_through = sold as f64 / (sold + on_hand) as f64 * 100.0;
+            if sell_through < threshold_percent {
+                slow_movers.push((product, sold, on_hand, sell_through));
             }
         }
+        slow_movers.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+        let mut table = ReportTable::new(
+            "Slow Movers",
+            &["Product Name", "Units Sold", "On Hand", "Sell-Through"],
+            "No products below the threshold.",
+        );
+        for (product, sold, on_hand, sell_through) in slow_movers {
+            table.rows.push(vec![
+                product.name.as_str().into(),
+                sold.into(),
+                on_hand.into(),
+                Value::Text(format!("{:.1}%", sell_through)),
+            ]);
+        }
+
+        let mut report = Report::new(&format!("Slow Movers ({})", range));
+        report.tables.push(table);
+        report
+            .summary
+            .push(("Sell-through threshold".to_string(), Value::Text(format!("{:.1}%", threshold_percent))));
+        report.note_class(class);
+        report
+    }
+
+    fn generate_dead_stock_report(&self, days: u32, class: Option<AbcClass>) -> Report {
+        let today = self.clock.now().date();
+        let mut table = ReportTable::new(
+            "Dead Stock",
+            &["Product Name", "Quantity", "Last Sale", "Days Since Sale", "Tied-Up Value"],
+            "No dead stock.",
+        );
+        let mut total_value = 0.0;
+        for product in self.products.iter().filter(|p| p.quantity > 0 && p.is_in_class(class)) {
+            let last_sale = self
+                .sales
+                .iter()
+                .filter(|s| s.product_name == product.name)
+                .map(|s| s.timestamp)
+                .max();
+            let days_since = last_sale.map(|timestamp| (today - timestamp.date()).num_days());
+            if days_since.is_some_and(|d| d < days as i64) {
+                continue;
+            }
+            let value = self.unit_cost_at(&product.name, today) * product.quantity as f64;
+            total_value += value;
+            table.rows.push(vec![
+                product.name.as_str().into(),
+                product.quantity.into(),
+                last_sale.map_or(Value::Text("Never".to_string()), Value::Date),
+                days_since.map_or(Value::Empty, Value::Integer),
+                Value::Money(value),
+            ]);
+        }
+        table.totals = Some(vec![
+            "Total".into(),
+            Value::Empty,
+            Value::Empty,
+            Value::Empty,
+            Value::Money(total_value),
+        ]);
+
+        let mut report = Report::new(&format!("Dead Stock (no sales in {} days)", days));
+        report.tables.push(table);
+        report.summary.push(("Tied-up value".to_string(), Value::Money(total_value)));
+        report.note_class(class);
+        report
+    }
+
+    fn generate_forecast_report(&self, settings: &ForecastSettings) -> Result<Report, InventoryError> {
+        settings.validate()?;
+        let mut table = ReportTable::new(
+            "Demand Forecast",
+            &["Product Name", "On Hand", "Forecast Demand", "Per Day", "Mean Abs. Error", "Suggested Order"],
+            "No products in inventory.",
+        );
+        let mut total_order = 0u32;
+        for product in self.products.iter().filter(|p| !p.archived) {
+            let forecast = self.forecast_demand(&product.name, settings)?;
+            table.rows.push(vec![
+                product.name.as_str().into(),
+                forecast.on_hand.into(),
+                Value::Text(format!("{:.1}", forecast.total_demand())),
+                Value::Text(format!("{:.2}", forecast.total_demand() / settings.horizon_days.max(1) as f64)),
+                Value::Text(format!("{:.2}", forecast.mean_absolute_error)),
+                forecast.suggested_order().into(),
+            ]);
+            total_order += forecast.suggested_order();
+        }
+
+        let mut report = Report::new(&format!("Demand Forecast (next {} days)", settings.horizon_days));
+        report.tables.push(table);
+        report.summary.push(("Method".to_string(), settings.method.to_string().into()));
+        report
+            .summary
+            .push(("History".to_string(), format!("{} days", settings.history_days).into()));
+        report.summary.push(("Units to reorder".to_string(), total_order.into()));
+        Ok(report)
+    }
+
+    // Changes still to come are listed as scheduled, in the order they take effect.
+    fn generate_price_history_report(&self, product_name: Option<&str>) -> Report {
+        let now = self.clock.now();
+        let mut table = ReportTable::new(
+            "Price Changes",
+            &["Effective", "Product Name", "Kind", "Previous Price", "Price", "Until", "User"],
+            "No price changes recorded.",
+        );
+        let mut changes: Vec<_> = self
+            .price_changes
+            .iter()
+            .filter(|c| product_name.is_none_or(|name| c.product_name == name))
+            .collect();
+        changes.sort_by_key(|c| c.effective);
+        for change in changes {
+            let kind = match (change.is_sale_price(), change.effective > now) {
+                (true, _) => "Sale Price",
+                (false, true) => "Scheduled",
+                (false, false) => "Change",
+            };
+            table.rows.push(vec![
+                change.effective.into(),
+                change.product_name.as_str().into(),
+                kind.into(),
+                Value::Money(change.previous_price),
+                Value::Money(change.price),
+                change.until.map_or(Value::Empty, Value::Date),
+                change.user.as_str().into(),
+            ]);
+        }
+
+        let mut report = Report::new("Price History");
+        report.tables.push(table);
+        if let Some(name) = product_name {
+            report.summary.push(("Product".to_string(), name.into()));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::returns::ReturnManagement;
+
+    #[test]
+    fn test_inventory_report_rows() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1200.0, 10).unwrap();
+
+        let report = inventory.generate_inventory_report(None);
+        assert_eq!(report.tables.len(), 1);
+        assert_eq!(report.tables[0].columns, vec!["Product Name", "Quantity", "Price", "Description", "Class"]);
+        assert_eq!(
+            report.tables[0].rows[0],
+            vec![
+                Value::Text("Laptop".to_string()),
+                Value::Integer(10),
+                Value::Money(1200.0),
+                Value::Text("High-performance laptop".to_string()),
+                Value::Empty,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sales_report_includes_returns_and_totals() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+        inventory.record_sale("Laptop", 2, 1200.0).unwrap();
+        inventory.record_return(1, 1, 0).unwrap();
+
+        let report = inventory.generate_sales_report(&DateRange::all());
+        let table = &report.tables[0];
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1][0], Value::Text("R1".to_string()));
+        assert_eq!(table.rows[1][3], Value::Integer(-1));
+        assert_eq!(table.rows[1][5], Value::Money(-1200.0));
+        assert_eq!(table.totals.as_ref().unwrap()[5], Value::Money(1200.0));
+        assert_eq!(report.summary[1], ("Net profit".to_string(), Value::Money(200.0)));
+    }
+
+    #[test]
+    fn test_sales_report_totals_match_receipt() {
+        use crate::checkout::{Basket, Checkout, Discount};
+
+        let mut inventory = Inventory::new();
+        inventory.add_product("Coffee".to_string(), "Ground, 250g".to_string(), 6.0, 10).unwrap();
+        inventory.add_product("Milk".to_string(), "1 litre".to_string(), 1.5, 10).unwrap();
+        let mut basket = Basket::default();
+        basket.add(&inventory, "Coffee", 1).unwrap();
+        basket.add(&inventory, "Milk", 2).unwrap();
+        basket.set_discount(Discount::Amount(1.0)).unwrap();
+        let receipt = inventory.complete_checkout(&basket, 10.0).unwrap();
+
+        let report = inventory.generate_sales_report(&DateRange::all());
+        let table = &report.tables[0];
+        let line_totals: Vec<Value> = table.rows.iter().map(|row| row[5].clone()).collect();
+        let expected: Vec<Value> = inventory.sales.iter().map(|s| Value::Money(s.total_sale)).collect();
+        assert_eq!(line_totals, expected);
+        match &table.totals.as_ref().unwrap()[5] {
+            Value::Money(total) => assert!((total - receipt.total).abs() < 1e-9),
+            other => panic!("unexpected total {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stock_take_report_not_found() {
+        let inventory = Inventory::new();
+        assert_eq!(
+            inventory.generate_stock_take_report(3).err(),
+            Some(InventoryError::StockTakeFailed("Stock take #3 not found".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_money_display() {
+        assert_eq!(Value::Money(12.5).to_string(), "$12.50");
+        assert_eq!(Value::Money(-3.0).to_string(), "-$3.00");
+        assert_eq!(Value::Empty.to_string(), "");
+    }
+
+    #[test]
+    fn test_profit_and_loss_by_category() {
+        use crate::adjustments::{AdjustmentManagement, AdjustmentReason};
+
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+        inventory.add_product("Mouse".to_string(), "Wireless mouse".to_string(), 20.0, 10).unwrap();
+        inventory.add_product("Desk".to_string(), "Oak desk".to_string(), 300.0, 2).unwrap();
+        inventory.set_product_category("Laptop", "Computers".to_string()).unwrap();
+        inventory.set_product_category("Mouse", "Computers".to_string()).unwrap();
+        inventory.record_sale("Laptop", 4, 1200.0).unwrap();
+        inventory.record_sale("Mouse", 5, 30.0).unwrap();
+        inventory.record_return(1, 1, 1).unwrap(); // one laptop restocked, one written off
+        inventory.adjust_stock("Desk", -1, AdjustmentReason::Damage, "manager").unwrap();
+
+        let report = inventory.generate_profit_and_loss_report(&DateRange::all(), Grouping::Category);
+        let table = &report.tables[0];
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(
+            table.rows[0],
+            vec![
+                Value::Text("Computers".to_string()),
+                Value::Money(4950.0),
+                Value::Money(-2400.0),
+                Value::Money(2550.0),
+                Value::Money(3100.0), // 4000 + 100 sold, less the restocked laptop
+                Value::Money(-550.0),
+                Value::Text("-21.6%".to_string()),
+                Value::Money(0.0),
+                Value::Money(-550.0),
+            ]
+        );
+        assert_eq!(table.rows[1][0], Value::Text("Uncategorized".to_string()));
+        assert_eq!(table.rows[1][7], Value::Money(-300.0));
+        assert_eq!(report.summary[3], ("Net result".to_string(), Value::Money(-850.0)));
+        assert_eq!(inventory.total_profit(), -550.0); // gross margin agrees with the running profit total
+    }
+
+    #[test]
+    fn test_valuation_report_as_of_past_date() {
+        use crate::clock::FixedClock;
+
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(day(1).and_hms_opt(9, 0, 0).unwrap())));
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 2).unwrap();
+        inventory.record_purchase("Laptop", 2, 800.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(5).and_hms_opt(9, 0, 0).unwrap()));
+        inventory.record_sale("Laptop", 3, 1200.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(6).and_hms_opt(9, 0, 0).unwrap()));
+        inventory.change_price("Laptop", 1100.0, "manager").unwrap();
+        inventory.add_product("Mouse".to_string(), "Wireless mouse".to_string(), 20.0, 5).unwrap();
+
+        let report = inventory.generate_valuation_report(day(4), None);
+        let table = &report.tables[0];
+        assert_eq!(table.rows[0][1], Value::Integer(4));
+        assert_eq!(table.rows[0][3], Value::Money(3200.0));
+        assert_eq!(table.rows[0][5], Value::Money(4000.0));
+
+        let report = inventory.generate_valuation_report(day(5), None);
+        assert_eq!(report.tables[0].rows.len(), 1); // the mouse was only added later
+        assert_eq!(report.tables[0].totals.as_ref().unwrap()[1], Value::Integer(1));
+        assert_eq!(report.summary[1], ("Value at retail".to_string(), Value::Money(1000.0)));
+    }
+
+    #[test]
+    fn test_valuation_report_includes_imported_history() {
+        use crate::clock::FixedClock;
+        use crate::import::{FileFormat, ImportManagement, ImportOptions};
+
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(today)));
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 1).unwrap();
+        let csv = "date,product,quantity,price\n2019-06-01,Laptop,5,800\n2020-02-01,Laptop,2,900\n";
+        let options = ImportOptions { update_stock: true, ..ImportOptions::default() };
+        inventory.import_purchases(csv.as_bytes(), FileFormat::Csv, &options).unwrap();
+
+        let report = inventory.generate_valuation_report(NaiveDate::from_ymd_opt(2019, 12, 31).unwrap(), None);
+        let table = &report.tables[0];
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(table.rows[0][1], Value::Integer(6));
+        assert_eq!(table.rows[0][2], Value::Money(800.0));
+
+        let report = inventory.generate_valuation_report(NaiveDate::from_ymd_opt(2019, 5, 31).unwrap(), None);
+        assert!(report.tables[0].rows.is_empty());
+    }
+
+    #[test]
+    fn test_top_sellers_and_slow_movers() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+        inventory.add_product("Mouse".to_string(), "Wireless mouse".to_string(), 20.0, 10).unwrap();
+        inventory.record_sale("Laptop", 2, 1200.0).unwrap();
+        inventory.record_sale("Mouse", 8, 30.0).unwrap();
+        inventory.record_return(2, 1, 0).unwrap();
+
+        let report = inventory.generate_top_sellers_report(&DateRange::all(), 1);
+        assert_eq!(report.tables.len(), 3);
+        assert_eq!(report.tables[0].rows[0][1], Value::Text("Mouse".to_string()));
+        assert_eq!(report.tables[0].rows[0][2], Value::Integer(7));
+        assert_eq!(report.tables[1].rows, vec![vec![
+            Value::Integer(1),
+            Value::Text("Laptop".to_string()),
+            Value::Integer(2),
+            Value::Money(2400.0),
+            Value::Money(400.0),
+        ]]);
+
+        let report = inventory.generate_slow_movers_report(&DateRange::all(), 50.0, None);
+        let table = &report.tables[0];
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(table.rows[0][0], Value::Text("Laptop".to_string()));
+        assert_eq!(table.rows[0][3], Value::Text("20.0%".to_string()));
+    }
+
+    #[test]
+    fn test_dead_stock_report() {
+        use crate::clock::FixedClock;
+
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(day(1))));
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 5).unwrap();
+        inventory.add_product("Mouse".to_string(), "Wireless mouse".to_string(), 20.0, 5).unwrap();
+        inventory.add_product("Cable".to_string(), "USB cable".to_string(), 5.0, 4).unwrap();
+        inventory.record_sale("Laptop", 1, 1200.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(25)));
+        inventory.record_sale("Mouse", 1, 30.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(31)));
+
+        let report = inventory.generate_dead_stock_report(14, None);
+        let table = &report.tables[0];
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0][0], Value::Text("Laptop".to_string()));
+        assert_eq!(table.rows[0][3], Value::Integer(30));
+        assert_eq!(table.rows[1][2], Value::Text("Never".to_string()));
+        assert_eq!(report.summary[0], ("Tied-up value".to_string(), Value::Money(4020.0)));
     }
 }
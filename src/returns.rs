@@ -0,0 +1,188 @@
+use crate::inventory::{Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct SaleReturn {
+    pub sale_id: u32,
+    pub product_name: String,
+    pub quantity_restocked: u32,
+    pub quantity_written_off: u32,
+    pub refund_amount: f64,
+    pub profit_reversed: f64,
+    pub timestamp: NaiveDateTime,
+}
+
+impl SaleReturn {
+    pub fn quantity_returned(&self) -> u32 {
+        self.quantity_restocked + self.quantity_written_off
+    }
+}
+
+#[allow(dead_code)]
+pub trait ReturnManagement {
+    fn record_return(
+        &mut self,
+        sale_id: u32,
+        quantity_restocked: u32,
+        quantity_written_off: u32,
+    ) -> Result<(), InventoryError>;
+    fn returned_quantity(&self, sale_id: u32) -> u32;
+    fn total_refunds(&self) -> f64;
+}
+
+impl ReturnManagement for Inventory {
+    fn record_return(
+        &mut self,
+        sale_id: u32,
+        quantity_restocked: u32,
+        quantity_written_off: u32,
+    ) -> Result<(), InventoryError> {
+        let quantity_returned = match quantity_restocked.checked_add(quantity_written_off) {
+            Some(0) => return Err(InventoryError::InvalidQuantity(0)),
+            Some(quantity) => quantity,
+            None => return Err(InventoryError::InvalidQuantity(u32::MAX)),
+        };
+
+        let already_returned = self.returned_quantity(sale_id);
+        let sale = match self.sales.iter().find(|s| s.id == sale_id) {
+            Some(sale) => sale,
+            None => return Err(InventoryError::ReturnFailed(format!("Sale #{} not found", sale_id))),
+        };
+
+        if already_returned
+            .checked_add(quantity_returned)
+            .is_none_or(|total| total > sale.quantity_sold)
+        {
+            return Err(InventoryError::ReturnFailed(format!(
+                "Only {} unit(s) of sale #{} can still be returned",
+                sale.quantity_sold - already_returned,
+                sale_id
+            )));
+        }
+
+        // Restocked units give back their revenue share of the profit, while
+        // written-off units also lose the cost of the goods that never come back.
+        let unit_profit = sale.profit / sale.quantity_sold as f64;
+        let unit_cost = sale.sale_price - unit_profit;
+        let refund_amount = sale.sale_price * quantity_returned as f64;
+        let profit_reversed = unit_profit * quantity_returned as f64 + unit_cost * quantity_written_off as f64;
+        let product_name = sale.product_name.clone();
+
+        if quantity_restocked > 0 {
+            match self.products.iter_mut().find(|p| p.name == product_name) {
+                Some(p) => p.quantity += quantity_restocked,
+                None => return Err(InventoryError::ProductNotFound(product_name)),
+            }
+        }
+
+        self.returns.push(SaleReturn {
+            sale_id,
+            product_name,
+            quantity_restocked,
+            quantity_written_off,
+            refund_amount,
+            profit_reversed,
+            timestamp: self.clock.now(),
+        });
+        Ok(())
+    }
+
+    fn returned_quantity(&self, sale_id: u32) -> u32 {
+        self.returns
+            .iter()
+            .filter(|r| r.sale_id == sale_id)
+            .map(|r| r.quantity_returned())
+            .sum()
+    }
+
+    fn total_refunds(&self) -> f64 {
+        self.returns.iter().map(|r| r.refund_amount).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+
+    fn inventory_with_sale() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory
+            .add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10)
+            .unwrap();
+        inventory.record_sale("Laptop", 4, 1200.0).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_record_return_restock() {
+        let mut inventory = inventory_with_sale();
+
+        let result = inventory.record_return(1, 2, 0);
+        assert!(result.is_ok());
+
+        assert_eq!(inventory.products[0].quantity, 8); // 10 - 4 sold + 2 restocked
+        let sale_return = inventory.returns.last().unwrap();
+        assert_eq!(sale_return.product_name, "Laptop");
+        assert_eq!(sale_return.refund_amount, 2400.0);
+        assert_eq!(sale_return.profit_reversed, 400.0);
+        assert_eq!(inventory.total_sales(), 4800.0 - 2400.0);
+        assert_eq!(inventory.total_profit(), 800.0 - 400.0);
+    }
+
+    #[test]
+    fn test_record_return_write_off() {
+        let mut inventory = inventory_with_sale();
+
+        inventory.record_return(1, 0, 1).unwrap();
+
+        assert_eq!(inventory.products[0].quantity, 6); // written-off units are not restocked
+        let sale_return = inventory.returns.last().unwrap();
+        assert_eq!(sale_return.refund_amount, 1200.0);
+        assert_eq!(sale_return.profit_reversed, 1200.0); // 200 profit + 1000 cost of the lost unit
+    }
+
+    #[test]
+    fn test_record_return_partial_limits() {
+        let mut inventory = inventory_with_sale();
+
+        inventory.record_return(1, 3, 0).unwrap();
+        assert_eq!(inventory.returned_quantity(1), 3);
+
+        let result = inventory.record_return(1, 1, 1);
+        assert_eq!(
+            result,
+            Err(InventoryError::ReturnFailed("Only 1 unit(s) of sale #1 can still be returned".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_record_return_sale_not_found() {
+        let mut inventory = inventory_with_sale();
+
+        let result = inventory.record_return(42, 1, 0);
+        assert_eq!(result, Err(InventoryError::ReturnFailed("Sale #42 not found".to_string())));
+
+        let result = inventory.record_return(1, 0, 0);
+        assert_eq!(result, Err(InventoryError::InvalidQuantity(0)));
+    }
+
+    #[test]
+    fn test_record_return_overflowing_quantities() {
+        let mut inventory = inventory_with_sale();
+
+        let result = inventory.record_return(1, u32::MAX, 1);
+        assert_eq!(result, Err(InventoryError::InvalidQuantity(u32::MAX)));
+
+        inventory.record_return(1, 3, 0).unwrap();
+        let result = inventory.record_return(1, u32::MAX, 0);
+        assert_eq!(
+            result,
+            Err(InventoryError::ReturnFailed("Only 1 unit(s) of sale #1 can still be returned".to_string()))
+        );
+        assert_eq!(inventory.returned_quantity(1), 3);
+        assert_eq!(inventory.products[0].quantity, 9);
+    }
+}
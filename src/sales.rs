@@ -1,24 +1,48 @@
+use crate::clock::DateRange;
 use crate::inventory::{Inventory, InventoryError};
+use crate::pricing::PriceManagement;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Sale {
-
+    pub id: u32,
     pub product_name: String,
     pub quantity_sold: u32,
     pub sale_price: f64,
     pub total_sale: f64,
     pub profit: f64,
+    pub timestamp: NaiveDateTime,
+    /// Sales rung up together at the checkout share a receipt.
+    pub receipt_id: u32,
 }
 #[allow(dead_code)]
 pub trait SalesManagement {
     
     fn record_sale(&mut self, product_name: &str, quantity_sold: u32, sale_price: f64) -> Result<(), InventoryError>;
+    /// Sells at the price in effect now, including any sale price, and returns it.
+    fn record_sale_at_current_price(&mut self, product_name: &str, quantity_sold: u32) -> Result<f64, InventoryError>;
     fn total_sales(&self) -> f64;
     fn total_profit(&self) -> f64;
+    fn total_sales_between(&self, range: &DateRange) -> f64;
+    fn total_profit_between(&self, range: &DateRange) -> f64;
 }
 
-impl SalesManagement for Inventory {
+impl Inventory {
+    pub(crate) fn next_receipt_id(&self) -> u32 {
+        self.sales.iter().map(|s| s.receipt_id).max().unwrap_or(0) + 1
+    }
 
-    fn record_sale(&mut self, product_name: &str, quantity_sold: u32, sale_price: f64) -> Result<(), InventoryError> {
+    /// Records one sale line. Checkout passes the rounded line total it charged,
+    /// so the recorded total matches the receipt rather than price times quantity.
+    pub(crate) fn sell(
+        &mut self,
+        product_name: &str,
+        quantity_sold: u32,
+        sale_price: f64,
+        total_sale: f64,
+        receipt_id: u32,
+    ) -> Result<(), InventoryError> {
         if quantity_sold == 0 || sale_price <= 0.0 {
             return Err(InventoryError::InvalidInput("Quantity sold or sale price cannot be zero or negative".to_string()));
         }
@@ -27,20 +51,25 @@ impl SalesManagement for Inventory {
 
         match product {
             Some(p) => {
+                if p.archived {
+                    return Err(InventoryError::SaleFailed(format!("'{}' is archived", product_name)));
+                }
                 if p.quantity < quantity_sold {
                     return Err(InventoryError::OutOfStock(product_name.to_string()));
                 }
                 p.quantity -= quantity_sold;
 
-                let total_sale = sale_price * quantity_sold as f64;
                 let profit = total_sale - (p.price * quantity_sold as f64); // Assuming cost price is the same as product price
                 
                 let sale = Sale {
+                    id: self.sales.len() as u32 + 1,
                     product_name: product_name.to_string(),
                     quantity_sold,
                     sale_price,
                     total_sale,
                     profit,
+                    timestamp: self.clock.now(),
+                    receipt_id,
                 };
                 self.sales.push(sale);
                 Ok(())
@@ -48,13 +77,61 @@ impl SalesManagement for Inventory {
             None => Err(InventoryError::ProductNotFound(product_name.to_string())),
         }
     }
+}
+
+impl SalesManagement for Inventory {
+
+    fn record_sale(&mut self, product_name: &str, quantity_sold: u32, sale_price: f64) -> Result<(), InventoryError> {
+        let receipt_id = self.next_receipt_id();
+        self.sell(product_name, quantity_sold, sale_price, sale_price * quantity_sold as f64, receipt_id)
+    }
+
+    fn record_sale_at_current_price(&mut self, product_name: &str, quantity_sold: u32) -> Result<f64, InventoryError> {
+        let sale_price = match self.current_price(product_name) {
+            Some(price) => price,
+            None => return Err(InventoryError::ProductNotFound(product_name.to_string())),
+        };
+        self.record_sale(product_name, quantity_sold, sale_price)?;
+        Ok(sale_price)
+    }
 
     fn total_sales(&self) -> f64 {
-        self.sales.iter().map(|sale| sale.total_sale as f64).sum()
+        self.total_sales_between(&DateRange::all())
     }
 
     fn total_profit(&self) -> f64 {
-        self.sales.iter().map(|sale| sale.profit).sum()
+        self.total_profit_between(&DateRange::all())
+    }
+
+    // Returns count in the period they were made, not the period of the original sale.
+    fn total_sales_between(&self, range: &DateRange) -> f64 {
+        let refunds: f64 = self
+            .returns
+            .iter()
+            .filter(|r| range.contains(&r.timestamp))
+            .map(|r| r.refund_amount)
+            .sum();
+        self.sales
+            .iter()
+            .filter(|sale| range.contains(&sale.timestamp))
+            .map(|sale| sale.total_sale)
+            .sum::<f64>()
+            - refunds
+    }
+
+    fn total_profit_between(&self, range: &DateRange) -> f64 {
+        let reversed: f64 = self
+            .returns
+            .iter()
+            .filter(|r| range.contains(&r.timestamp))
+            .map(|r| r.profit_reversed)
+            .sum();
+        self.sales
+            .iter()
+            .filter(|sale| range.contains(&sale.timestamp))
+            .map(|sale| sale.profit)
+            .sum::<f64>()
+            - reversed
     }
 
 }
@@ -62,9 +139,12 @@ impl SalesManagement for Inventory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::FixedClock;
     use crate::inventory::{Inventory, InventoryManagement};
+    use chrono::NaiveDate;
 
     #[test]
+    #[allow(clippy::unnecessary_cast)] // the cast spells out the expected arithmetic
     fn test_record_sale_success() {
         let mut inventory = Inventory::new();
         match inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10){
@@ -153,4 +233,21 @@ mod tests {
         let total_profit = inventory.total_profit();
         assert_eq!(total_profit, (2400.0 - 2000.0) + (2550.0 - 2400.0)); // Total Sale - Cost Price
     }
+
+    #[test]
+    fn test_totals_between_dates() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(day(1).and_hms_opt(9, 0, 0).unwrap())));
+        inventory.add_product("Laptop".to_string(), "High-performance laptop".to_string(), 1000.0, 10).unwrap();
+
+        inventory.record_sale("Laptop", 1, 1200.0).unwrap();
+        inventory.clock = Box::new(FixedClock(day(8).and_hms_opt(17, 30, 0).unwrap()));
+        inventory.record_sale("Laptop", 2, 1100.0).unwrap();
+
+        assert_eq!(inventory.sales[1].timestamp, day(8).and_hms_opt(17, 30, 0).unwrap());
+        assert_eq!(inventory.total_sales_between(&DateRange::new(day(1), day(7))), 1200.0);
+        assert_eq!(inventory.total_sales_between(&DateRange::new(day(8), day(8))), 2200.0);
+        assert_eq!(inventory.total_profit_between(&DateRange::new(day(2), day(31))), 200.0);
+        assert_eq!(inventory.total_sales(), 3400.0);
+    }
 }
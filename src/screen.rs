@@ -0,0 +1,684 @@
+use crate::history::{History, Operation};
+use crate::input;
+use crate::inventory::{Inventory, InventoryError, InventoryManagement, Product};
+use crate::pricing::PriceManagement;
+use crate::purchases::PurchaseManagement;
+use crate::sales::SalesManagement;
+use crate::search::{ProductFilter, ProductSearch};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Flex, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Ordering;
+use std::io;
+
+/// Columns of the product table, in display order.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortColumn {
+    Name,
+    Sku,
+    Category,
+    Quantity,
+    Price,
+    Class,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 6] = [
+        SortColumn::Name,
+        SortColumn::Sku,
+        SortColumn::Category,
+        SortColumn::Quantity,
+        SortColumn::Price,
+        SortColumn::Class,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            SortColumn::Name => "Name",
+            SortColumn::Sku => "SKU",
+            SortColumn::Category => "Category",
+            SortColumn::Quantity => "Quantity",
+            SortColumn::Price => "Price",
+            SortColumn::Class => "Class",
+        }
+    }
+
+    fn compare(&self, a: &Product, b: &Product) -> Ordering {
+        match self {
+            SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortColumn::Sku => a.sku.cmp(&b.sku),
+            SortColumn::Category => a.category.to_lowercase().cmp(&b.category.to_lowercase()),
+            SortColumn::Quantity => a.quantity.cmp(&b.quantity),
+            SortColumn::Price => a.price.total_cmp(&b.price),
+            SortColumn::Class => class_label(a).cmp(&class_label(b)),
+        }
+    }
+}
+
+fn class_label(product: &Product) -> String {
+    product.abc_class.map(|c| c.to_string()).unwrap_or_default()
+}
+
+enum FormKind {
+    Add,
+    Edit(String),
+    Sale,
+    Purchase,
+    Filter,
+}
+
+/// A modal form: one text field per line, edited in place and validated on Enter.
+struct Form {
+    kind: FormKind,
+    fields: Vec<(&'static str, String)>,
+    focus: usize,
+    error: Option<String>,
+}
+
+impl Form {
+    fn new(kind: FormKind, fields: Vec<(&'static str, String)>) -> Self {
+        Form {
+            kind,
+            fields,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    fn title(&self) -> String {
+        match &self.kind {
+            FormKind::Add => "Add Product".to_string(),
+            FormKind::Edit(name) => format!("Edit {}", name),
+            FormKind::Sale => "Record Sale".to_string(),
+            FormKind::Purchase => "Record Purchase".to_string(),
+            FormKind::Filter => "Filter Products".to_string(),
+        }
+    }
+
+    fn value(&self, label: &str) -> &str {
+        self.fields
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map_or("", |(_, value)| value.trim())
+    }
+
+    /// The filter described by a filter form, keeping the search text and
+    /// toggles of `current`.
+    fn filter(&self, current: &ProductFilter) -> Result<ProductFilter, String> {
+        let price = |label| input::parse_optional(self.value(label), input::parse_money);
+        Ok(ProductFilter {
+            category: Some(self.value("Category").to_string()).filter(|c| !c.is_empty()),
+            min_price: price("Min Price")?,
+            max_price: price("Max Price")?,
+            ..current.clone()
+        })
+    }
+
+    /// Applies the form to the inventory, recording it for undo, and returns the
+    /// status message to show, or the reason the form stays open.
+    fn submit(&self, inventory: &mut Inventory, user: &str, history: &mut History) -> Result<String, String> {
+        match &self.kind {
+            FormKind::Add => {
+                let name = input::parse_text(self.value("Name"))?;
+                let price = input::parse_money(self.value("Price"))?;
+                let quantity = input::parse_whole_number(self.value("Quantity"))?;
+                let reorder_level = input::parse_optional(self.value("Reorder Level"), input::parse_whole_number)?;
+                inventory
+                    .check_sku_available(&name, self.value("SKU"))
+                    .and_then(|()| inventory.add_product(name.clone(), self.value("Description").to_string(), price, quantity))
+                    .and_then(|()| inventory.set_product_category(&name, self.value("Category").to_string()))
+                    .and_then(|()| inventory.set_product_sku(&name, self.value("SKU").to_string()))
+                    .and_then(|()| inventory.set_reorder_level(&name, reorder_level.unwrap_or(0)))
+                    .map_err(|e| e.to_string())?;
+                if let Some(product) = inventory.products.iter().find(|p| p.name == name) {
+                    history.record(Operation::AddProduct { product: product.clone(), price_changes: Vec::new() });
+                }
+                Ok(format!("Added {}.", name))
+            }
+            FormKind::Edit(original) => {
+                let before = match inventory.products.iter().find(|p| &p.name == original) {
+                    Some(product) => product.clone(),
+                    None => return Err(InventoryError::ProductNotFound(original.clone()).to_string()),
+                };
+                let name = input::parse_text(self.value("Name"))?;
+                // The price field shows the price rounded to the cent, so it is only
+                // submitted when changed; otherwise a sub-cent price would be rounded.
+                let price = match self.value("Price") {
+                    text if text == format!("{:.2}", before.price) => None,
+                    text => Some(input::parse_money(text)?),
+                };
+                // Stock is only overwritten when the quantity was actually changed,
+                // which also keeps sold-out products editable.
+                let quantity = match self.value("Quantity") {
+                    text if text == before.quantity.to_string() => None,
+                    text => Some(input::parse_whole_number(text)?),
+                };
+                let reorder_level = input::parse_optional(self.value("Reorder Level"), input::parse_whole_number)?;
+                inventory
+                    .check_sku_available(original, self.value("SKU"))
+                    .and_then(|()| {
+                        inventory.edit_product(
+                            original,
+                            Some(name.clone()),
+                            Some(self.value("Description").to_string()),
+                            price,
+                            quantity,
+                            user,
+                        )
+                    })
+                    .and_then(|()| inventory.set_product_category(&name, self.value("Category").to_string()))
+                    .and_then(|()| inventory.set_product_sku(&name, self.value("SKU").to_string()))
+                    .and_then(|()| inventory.set_reorder_level(&name, reorder_level.unwrap_or(0)))
+                    .map_err(|e| e.to_string())?;
+                if let Some(after) = inventory.products.iter().find(|p| p.name == name) {
+                    history.record(Operation::EditProduct { before, after: after.clone() });
+                }
+                Ok(format!("Updated {}.", name))
+            }
+            FormKind::Sale => {
+                let name = input::parse_text(self.value("Product"))?;
+                let quantity = input::parse_whole_number(self.value("Quantity"))?;
+                let price = input::parse_money(self.value("Sale Price"))?;
+                inventory.record_sale(&name, quantity, price).map_err(|e| e.to_string())?;
+                history.record(Operation::Sale(inventory.sales[inventory.sales.len() - 1..].to_vec()));
+                Ok(format!("Sold {} x {}.", quantity, name))
+            }
+            FormKind::Purchase => {
+                let name = input::parse_text(self.value("Product"))?;
+                let quantity = input::parse_whole_number(self.value("Quantity"))?;
+                let price = input::parse_money(self.value("Purchase Price"))?;
+                inventory
+                    .record_purchase_from(self.value("Supplier"), &name, quantity, price)
+                    .map_err(|e| e.to_string())?;
+                history.record(Operation::Purchase(inventory.purchases[inventory.purchases.len() - 1].clone()));
+                Ok(format!("Received {} x {}.", quantity, name))
+            }
+            FormKind::Filter => Err("Filter forms change the view, not the inventory.".to_string()),
+        }
+    }
+}
+
+/// Full-screen product view: a sortable, scrollable table with modal forms and
+/// a status bar. Key handling is kept apart from drawing so it can be tested
+/// without a terminal.
+pub struct Screen {
+    user: String,
+    sort: SortColumn,
+    descending: bool,
+    table: TableState,
+    form: Option<Form>,
+    status: String,
+    filter: ProductFilter,
+    /// Whether typed characters go to the search text rather than the key bindings.
+    searching: bool,
+    /// Shared with the menus, so changes made here can be undone there.
+    history: History,
+}
+
+impl Screen {
+    pub fn new(user: &str, history: History) -> Self {
+        Screen {
+            user: user.to_string(),
+            sort: SortColumn::Name,
+            descending: false,
+            table: TableState::default().with_selected(Some(0)),
+            form: None,
+            status: "Welcome.".to_string(),
+            filter: ProductFilter::default(),
+            searching: false,
+            history,
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal, inventory: &mut Inventory) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame, inventory))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key, inventory) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Products passing the current filter, in the current sort order.
+    fn sorted<'a>(&self, inventory: &'a Inventory) -> Vec<&'a Product> {
+        let mut products = inventory.search_products(&self.filter);
+        products.sort_by(|a, b| {
+            let order = self.sort.compare(a, b);
+            if self.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        products
+    }
+
+    fn selected<'a>(&self, inventory: &'a Inventory) -> Option<&'a Product> {
+        self.table.selected().and_then(|index| self.sorted(inventory).get(index).copied())
+    }
+
+    /// Handles one key press. Returns false when the user leaves the screen.
+    pub fn handle_key(&mut self, key: KeyEvent, inventory: &mut Inventory) -> bool {
+        if self.form.is_some() {
+            self.handle_form_key(key, inventory);
+            return true;
+        }
+        if self.searching {
+            self.handle_search_key(key);
+            return true;
+        }
+
+        let count = self.sorted(inventory).len();
+        let selected = self.table.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.table.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => self.table.select(Some((selected + 1).min(count.saturating_sub(1)))),
+            KeyCode::PageUp => self.table.select(Some(selected.saturating_sub(10))),
+            KeyCode::PageDown => self.table.select(Some((selected + 10).min(count.saturating_sub(1)))),
+            KeyCode::Home => self.table.select(Some(0)),
+            KeyCode::End => self.table.select(Some(count.saturating_sub(1))),
+            KeyCode::Left | KeyCode::Right => {
+                let position = SortColumn::ALL.iter().position(|c| *c == self.sort).unwrap_or(0);
+                let next = if key.code == KeyCode::Left {
+                    position + SortColumn::ALL.len() - 1
+                } else {
+                    position + 1
+                };
+                self.sort = SortColumn::ALL[next % SortColumn::ALL.len()];
+                self.status = format!("Sorted by {}.", self.sort.title());
+            }
+            KeyCode::Char('r') => self.descending = !self.descending,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.status = "Type to search, Enter to keep, Esc to clear.".to_string();
+            }
+            KeyCode::Char('i') => {
+                self.filter.in_stock_only = !self.filter.in_stock_only;
+                self.table.select(Some(0));
+            }
+            KeyCode::Char('l') => {
+                self.filter.below_reorder_level = !self.filter.below_reorder_level;
+                self.table.select(Some(0));
+            }
+            KeyCode::Char('f') => {
+                let price = |price: Option<f64>| price.map(|p| format!("{:.2}", p)).unwrap_or_default();
+                self.form = Some(Form::new(
+                    FormKind::Filter,
+                    vec![
+                        ("Category", self.filter.category.clone().unwrap_or_default()),
+                        ("Min Price", price(self.filter.min_price)),
+                        ("Max Price", price(self.filter.max_price)),
+                    ],
+                ))
+            }
+            KeyCode::Char('a') => {
+                self.form = Some(Form::new(
+                    FormKind::Add,
+                    vec![
+                        ("Name", String::new()),
+                        ("Description", String::new()),
+                        ("Price", String::new()),
+                        ("Quantity", String::new()),
+                        ("Category", String::new()),
+                        ("SKU", String::new()),
+                        ("Reorder Level", String::new()),
+                    ],
+                ))
+            }
+            KeyCode::Char('e') | KeyCode::Enter => match self.selected(inventory) {
+                Some(product) => {
+                    self.form = Some(Form::new(
+                        FormKind::Edit(product.name.clone()),
+                        vec![
+                            ("Name", product.name.clone()),
+                            ("Description", product.description.clone()),
+                            ("Price", format!("{:.2}", product.price)),
+                            ("Quantity", product.quantity.to_string()),
+                            ("Category", product.category.clone()),
+                            ("SKU", product.sku.clone()),
+                            ("Reorder Level", product.reorder_level.to_string()),
+                        ],
+                    ))
+                }
+                None => self.status = "No product selected.".to_string(),
+            },
+            KeyCode::Char('s') => {
+                let product = self.selected(inventory);
+                let price = product.and_then(|p| inventory.current_price(&p.name));
+                self.form = Some(Form::new(
+                    FormKind::Sale,
+                    vec![
+                        ("Product", product.map(|p| p.name.clone()).unwrap_or_default()),
+                        ("Quantity", "1".to_string()),
+                        ("Sale Price", price.map(|price| format!("{:.2}", price)).unwrap_or_default()),
+                    ],
+                ))
+            }
+            KeyCode::Char('p') => {
+                let product = self.selected(inventory);
+                self.form = Some(Form::new(
+                    FormKind::Purchase,
+                    vec![
+                        ("Supplier", String::new()),
+                        ("Product", product.map(|p| p.name.clone()).unwrap_or_default()),
+                        ("Quantity", String::new()),
+                        ("Purchase Price", String::new()),
+                    ],
+                ))
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, inventory: &mut Inventory) {
+        let form = match self.form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+        let field_count = form.fields.len();
+        match key.code {
+            KeyCode::Esc => {
+                self.form = None;
+                self.status = "Cancelled.".to_string();
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % field_count,
+            KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + field_count - 1) % field_count,
+            KeyCode::Backspace => {
+                form.fields[form.focus].1.pop();
+            }
+            KeyCode::Char(c) => form.fields[form.focus].1.push(c),
+            KeyCode::Enter if matches!(form.kind, FormKind::Filter) => match form.filter(&self.filter) {
+                Ok(filter) => {
+                    self.filter = filter;
+                    self.form = None;
+                    self.table.select(Some(0));
+                    self.status = "Filter applied.".to_string();
+                }
+                Err(error) => form.error = Some(error),
+            },
+            KeyCode::Enter => match form.submit(inventory, &self.user, &mut self.history) {
+                Ok(message) => {
+                    self.form = None;
+                    self.status = message;
+                }
+                Err(error) => form.error = Some(error),
+            },
+            _ => {}
+        }
+    }
+
+    // The table narrows as each character is typed.
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter.query.clear();
+                self.searching = false;
+                self.status = "Search cleared.".to_string();
+            }
+            KeyCode::Enter => {
+                self.searching = false;
+                self.status = format!("Searching for '{}'.", self.filter.query);
+            }
+            KeyCode::Backspace => {
+                self.filter.query.pop();
+            }
+            KeyCode::Char(c) => self.filter.query.push(c),
+            _ => return,
+        }
+        self.table.select(Some(0));
+    }
+
+    /// A short description of the active filters for the table title.
+    fn filter_summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.filter.query.is_empty() || self.searching {
+            parts.push(format!("/{}", self.filter.query));
+        }
+        if self.filter.in_stock_only {
+            parts.push("in stock".to_string());
+        }
+        if self.filter.below_reorder_level {
+            parts.push("below reorder level".to_string());
+        }
+        if let Some(category) = &self.filter.category {
+            parts.push(format!("category {}", category));
+        }
+        match (self.filter.min_price, self.filter.max_price) {
+            (Some(min), Some(max)) => parts.push(format!("${:.2}-${:.2}", min, max)),
+            (Some(min), None) => parts.push(format!("from ${:.2}", min)),
+            (None, Some(max)) => parts.push(format!("up to ${:.2}", max)),
+            (None, None) => {}
+        }
+        parts.join(", ")
+    }
+
+    fn draw(&mut self, frame: &mut Frame, inventory: &Inventory) {
+        let [table_area, status_area] = Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+        let header = Row::new(SortColumn::ALL.iter().map(|column| {
+            let title = column.title().to_string();
+            match (*column == self.sort, self.descending) {
+                (true, false) => format!("{} ▲", title),
+                (true, true) => format!("{} ▼", title),
+                (false, _) => title,
+            }
+        }))
+        .style(Style::new().add_modifier(Modifier::BOLD));
+        let products = self.sorted(inventory);
+        let title = match self.filter_summary() {
+            summary if summary.is_empty() => format!(" Products ({}) ", products.len()),
+            summary => format!(" Products ({} of {}) | {} ", products.len(), inventory.products.len(), summary),
+        };
+        let rows = products.into_iter().map(|product| {
+            let quantity = if product.is_below_reorder_level() {
+                format!("{} !", product.quantity)
+            } else {
+                product.quantity.to_string()
+            };
+            Row::new(vec![
+                product.name.clone(),
+                product.sku.clone(),
+                product.category.clone(),
+                quantity,
+                format!("${:.2}", product.price),
+                class_label(product),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(7),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let keys = if self.searching {
+            "Type to search  Enter Keep  Esc Clear"
+        } else {
+            "↑↓ Move  ←→ Sort  / Search  i In stock  l Low  f Filter  a Add  e Edit  s Sale  p Purchase  q Back"
+        };
+        let status = Line::from(format!(" {} | {} | {}", self.user, keys, self.status)).reversed();
+        frame.render_widget(status, status_area);
+
+        if let Some(form) = &self.form {
+            let height = form.fields.len() as u16 + 4;
+            let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(frame.area());
+            let [area] = Layout::horizontal([Constraint::Length(60)]).flex(Flex::Center).areas(area);
+            let mut lines: Vec<Line> = form
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, (label, value))| {
+                    let line = Line::from(format!("{:>15}: {}", label, value));
+                    if i == form.focus {
+                        line.bold()
+                    } else {
+                        line
+                    }
+                })
+                .collect();
+            lines.push(Line::from(match &form.error {
+                Some(error) => error.clone(),
+                None => "Enter to save, Esc to cancel, Tab for next field.".to_string(),
+            }));
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::bordered().title(format!(" {} ", form.title()))),
+                area,
+            );
+        }
+    }
+}
+
+/// Takes over the terminal until the user leaves the screen, restoring it
+/// afterwards even if drawing fails.
+pub fn run(inventory: &mut Inventory, user: &str, history: &mut History) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let mut screen = Screen::new(user, std::mem::take(history));
+    let result = screen.run(&mut terminal, inventory);
+    ratatui::restore();
+    *history = screen.history;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(screen: &mut Screen, inventory: &mut Inventory, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                '\u{1b}' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            screen.handle_key(KeyEvent::new(code, KeyModifiers::NONE), inventory);
+        }
+    }
+
+    #[test]
+    fn test_add_product_form() {
+        let mut inventory = Inventory::new();
+        let mut screen = Screen::new("admin", History::default());
+
+        press(&mut screen, &mut inventory, "aLamp\tDesk lamp\t12,5\tx\n");
+        assert_eq!(screen.form.as_ref().unwrap().error, Some("'x' is not a valid whole number.".to_string()));
+
+        let key = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+        screen.handle_key(key, &mut inventory);
+        press(&mut screen, &mut inventory, "3\tLighting\n");
+        assert!(screen.form.is_none());
+        assert_eq!(inventory.products[0].price, 12.5);
+        assert_eq!(inventory.products[0].category, "Lighting");
+        assert_eq!(screen.status, "Added Lamp.");
+    }
+
+    #[test]
+    fn test_taken_sku_adds_nothing() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Lamp".to_string(), String::new(), 20.0, 2).unwrap();
+        inventory.set_product_sku("Lamp", "LMP-1".to_string()).unwrap();
+        let mut screen = Screen::new("admin", History::default());
+
+        press(&mut screen, &mut inventory, "aMug\t\t3\t2\t\tLMP-1\n");
+        assert_eq!(
+            screen.form.as_ref().unwrap().error,
+            Some("SKU 'LMP-1' is already used by 'Lamp'".to_string())
+        );
+        assert_eq!(inventory.products.len(), 1);
+    }
+
+    #[test]
+    fn test_edit_sold_out_product() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Mug".to_string(), String::new(), 5.0, 2).unwrap();
+        inventory.record_sale("Mug", 2, 5.0).unwrap();
+        let mut screen = Screen::new("admin", History::default());
+
+        press(&mut screen, &mut inventory, "e\tBlue mug\n");
+        assert!(screen.form.is_none(), "{:?}", screen.form.as_ref().and_then(|f| f.error.clone()));
+        assert_eq!(inventory.products[0].description, "Blue mug");
+        assert_eq!(inventory.products[0].quantity, 0);
+        assert_eq!(screen.status, "Updated Mug.");
+
+        screen.history.undo(&mut inventory, "admin").unwrap();
+        assert_eq!(inventory.products[0].description, "");
+    }
+
+    #[test]
+    fn test_edit_keeps_sub_cent_price() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Mug".to_string(), String::new(), 12.345, 2).unwrap();
+        let mut screen = Screen::new("admin", History::default());
+
+        press(&mut screen, &mut inventory, "e\tBlue mug\n");
+        assert!(screen.form.is_none(), "{:?}", screen.form.as_ref().and_then(|f| f.error.clone()));
+        assert_eq!(inventory.products[0].description, "Blue mug");
+        assert_eq!(inventory.products[0].price, 12.345);
+        assert!(inventory.price_changes.is_empty());
+    }
+
+    #[test]
+    fn test_sort_and_sale_from_selection() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Mug".to_string(), String::new(), 5.0, 10).unwrap();
+        inventory.add_product("Lamp".to_string(), String::new(), 20.0, 10).unwrap();
+        let mut screen = Screen::new("admin", History::default());
+
+        press(&mut screen, &mut inventory, "rr");
+        assert_eq!(screen.selected(&inventory).unwrap().name, "Lamp");
+
+        // Sort by price, highest first, then move down to the mug.
+        let right = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+        for _ in 0..4 {
+            screen.handle_key(right, &mut inventory);
+        }
+        press(&mut screen, &mut inventory, "rj");
+        assert_eq!(screen.sort, SortColumn::Price);
+        assert_eq!(screen.selected(&inventory).unwrap().name, "Mug");
+
+        press(&mut screen, &mut inventory, "s\t\t\n");
+        assert_eq!(inventory.sales.len(), 1);
+        assert_eq!(inventory.products[0].quantity, 9);
+        assert!(matches!(screen.history.next_undo(), Some(Operation::Sale(_))));
+        assert!(!screen.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &mut inventory));
+    }
+
+    #[test]
+    fn test_search_and_filters() {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Desk Lamp".to_string(), String::new(), 20.0, 2).unwrap();
+        inventory.add_product("Mug".to_string(), String::new(), 5.0, 10).unwrap();
+        inventory.add_product("Lampshade".to_string(), String::new(), 8.0, 10).unwrap();
+        inventory.set_reorder_level("Desk Lamp", 3).unwrap();
+        let mut screen = Screen::new("admin", History::default());
+
+        // While searching, letters are search text rather than key bindings.
+        press(&mut screen, &mut inventory, "/lamp\n");
+        let names: Vec<&str> = screen.sorted(&inventory).iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Desk Lamp", "Lampshade"]);
+
+        press(&mut screen, &mut inventory, "l");
+        assert_eq!(screen.sorted(&inventory).len(), 1);
+        press(&mut screen, &mut inventory, "l/\u{1b}f\t\t10\n");
+        let names: Vec<&str> = screen.sorted(&inventory).iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Lampshade", "Mug"]);
+        assert_eq!(screen.filter_summary(), "up to $10.00");
+    }
+}
@@ -0,0 +1,175 @@
+use crate::inventory::{Inventory, Product};
+use std::cmp::Reverse;
+
+/// Which products to show. Every condition that is set must hold; the default
+/// matches everything.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProductFilter {
+    /// Fuzzy text matched against name, SKU and description.
+    pub query: String,
+    pub in_stock_only: bool,
+    pub below_reorder_level: bool,
+    pub category: Option<String>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// Also show archived products, which are hidden by default.
+    pub include_archived: bool,
+}
+
+impl ProductFilter {
+    fn allows(&self, product: &Product) -> bool {
+        (self.include_archived || !product.archived)
+            && (!self.in_stock_only || product.quantity > 0)
+            && (!self.below_reorder_level || product.is_below_reorder_level())
+            && self
+                .category
+                .as_ref()
+                .is_none_or(|category| product.category.eq_ignore_ascii_case(category))
+            && self.min_price.is_none_or(|min| product.price >= min)
+            && self.max_price.is_none_or(|max| product.price <= max)
+    }
+}
+
+/// Scores how well `query` matches `text` when its characters appear in order,
+/// not necessarily next to each other, ignoring case. Runs of adjacent characters
+/// and matches at the start of a word score higher. `None` means no match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// The slice of `items` on a zero-based `page`, and the total number of pages.
+/// Pages past the end come back empty.
+pub fn paginate<T>(items: &[T], page: usize, page_size: usize) -> (&[T], usize) {
+    let page_size = page_size.max(1);
+    let total_pages = items.len().div_ceil(page_size).max(1);
+    let start = (page * page_size).min(items.len());
+    let end = (start + page_size).min(items.len());
+    (&items[start..end], total_pages)
+}
+
+pub trait ProductSearch {
+    fn search_products(&self, filter: &ProductFilter) -> Vec<&Product>;
+}
+
+impl ProductSearch for Inventory {
+    // With a query, the best matches come first; otherwise catalog order is kept.
+    fn search_products(&self, filter: &ProductFilter) -> Vec<&Product> {
+        let mut matches: Vec<(u32, &Product)> = self
+            .products
+            .iter()
+            .filter(|product| filter.allows(product))
+            .filter_map(|product| {
+                if filter.query.trim().is_empty() {
+                    return Some((0, product));
+                }
+                [&product.name, &product.sku, &product.description]
+                    .into_iter()
+                    .filter_map(|text| fuzzy_score(&filter.query, text))
+                    .max()
+                    .map(|score| (score, product))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches.into_iter().map(|(_, product)| product).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+
+    fn inventory_with_catalog() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Wireless Mouse".to_string(), "Bluetooth mouse".to_string(), 25.0, 10).unwrap();
+        inventory.add_product("Mouse Pad".to_string(), "Cloth pad".to_string(), 8.0, 1).unwrap();
+        inventory.add_product("USB Cable".to_string(), "1m cable".to_string(), 5.0, 3).unwrap();
+        inventory.add_product("Monitor".to_string(), "27-inch display".to_string(), 300.0, 2).unwrap();
+        inventory.set_product_sku("USB Cable", "CAB-001".to_string()).unwrap();
+        inventory.set_reorder_level("USB Cable", 5).unwrap();
+        inventory.set_product_category("Monitor", "Displays".to_string()).unwrap();
+        inventory.products[1].quantity = 0; // sold out
+        inventory
+    }
+
+    fn names(products: Vec<&Product>) -> Vec<&str> {
+        products.into_iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("wms", "Wireless Mouse").is_some());
+        assert!(fuzzy_score("msw", "Wireless Mouse").is_none());
+        assert!(fuzzy_score("mou", "Mouse Pad") > fuzzy_score("mou", "Monitor stand, blue"));
+    }
+
+    #[test]
+    fn test_search_products() {
+        let inventory = inventory_with_catalog();
+
+        let mut filter = ProductFilter {
+            query: "mouse".to_string(),
+            ..ProductFilter::default()
+        };
+        assert_eq!(names(inventory.search_products(&filter)), vec!["Wireless Mouse", "Mouse Pad"]);
+        filter.in_stock_only = true;
+        assert_eq!(names(inventory.search_products(&filter)), vec!["Wireless Mouse"]);
+
+        let filter = ProductFilter {
+            query: "cab0".to_string(),
+            ..ProductFilter::default()
+        };
+        assert_eq!(names(inventory.search_products(&filter)), vec!["USB Cable"]);
+    }
+
+    #[test]
+    fn test_archived_products_are_hidden() {
+        let mut inventory = inventory_with_catalog();
+        inventory.archive_product("Monitor").unwrap();
+
+        let mut filter = ProductFilter::default();
+        assert_eq!(names(inventory.search_products(&filter)), vec!["Wireless Mouse", "Mouse Pad", "USB Cable"]);
+        filter.include_archived = true;
+        assert_eq!(inventory.search_products(&filter).len(), 4);
+    }
+
+    #[test]
+    fn test_filters_and_pagination() {
+        let inventory = inventory_with_catalog();
+
+        let filter = ProductFilter {
+            below_reorder_level: true,
+            ..ProductFilter::default()
+        };
+        assert_eq!(names(inventory.search_products(&filter)), vec!["USB Cable"]);
+
+        let filter = ProductFilter {
+            category: Some("displays".to_string()),
+            min_price: Some(100.0),
+            ..ProductFilter::default()
+        };
+        assert_eq!(names(inventory.search_products(&filter)), vec!["Monitor"]);
+
+        let items: Vec<u32> = (1..=25).collect();
+        assert_eq!(paginate(&items, 0, 10), (&items[0..10], 3));
+        assert_eq!(paginate(&items, 2, 10), (&items[20..25], 3));
+        assert_eq!(paginate(&items, 5, 10).0.len(), 0);
+        assert_eq!(paginate::<u32>(&[], 0, 10).1, 1);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::abc::{AbcClass, AbcManagement};
+use crate::adjustments::{AdjustmentManagement, AdjustmentReason};
+use crate::inventory::{Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct StockTakeLine {
+    pub product_name: String,
+    pub expected_quantity: u32,
+    pub counted_quantity: Option<u32>,
+    pub unit_cost: f64,
+}
+
+impl StockTakeLine {
+    pub fn variance(&self) -> Option<i64> {
+        self.counted_quantity
+            .map(|counted| counted as i64 - self.expected_quantity as i64)
+    }
+
+    pub fn variance_value(&self) -> f64 {
+        self.variance().unwrap_or(0) as f64 * self.unit_cost
+    }
+}
+
+/// A count of a set of products against the quantities frozen when it was
+/// started. Counts can be entered over several sessions until it is posted.
+#[derive(Serialize, Deserialize)]
+pub struct StockTake {
+    pub id: u32,
+    pub started_at: NaiveDateTime,
+    pub lines: Vec<StockTakeLine>,
+    pub posted: bool,
+}
+
+#[allow(dead_code)]
+pub trait StockTakeManagement {
+    fn start_stock_take(&mut self, product_names: &[String]) -> Result<u32, InventoryError>;
+    fn start_category_stock_take(&mut self, category: &str) -> Result<u32, InventoryError>;
+    fn start_class_stock_take(&mut self, class: AbcClass) -> Result<u32, InventoryError>;
+    fn record_count(&mut self, stock_take_id: u32, product_name: &str, counted_quantity: u32) -> Result<(), InventoryError>;
+    fn post_stock_take(&mut self, stock_take_id: u32, user: &str) -> Result<(), InventoryError>;
+}
+
+impl Inventory {
+    fn open_stock_take(&mut self, stock_take_id: u32) -> Result<&mut StockTake, InventoryError> {
+        match self.stock_takes.iter_mut().find(|t| t.id == stock_take_id) {
+            Some(stock_take) if stock_take.posted => Err(InventoryError::StockTakeFailed(format!(
+                "Stock take #{} has already been posted",
+                stock_take_id
+            ))),
+            Some(stock_take) => Ok(stock_take),
+            None => Err(InventoryError::StockTakeFailed(format!(
+                "Stock take #{} not found",
+                stock_take_id
+            ))),
+        }
+    }
+}
+
+impl StockTakeManagement for Inventory {
+    fn start_stock_take(&mut self, product_names: &[String]) -> Result<u32, InventoryError> {
+        if product_names.is_empty() {
+            return Err(InventoryError::StockTakeFailed("No products selected for counting".to_string()));
+        }
+
+        let mut lines = Vec::new();
+        for name in product_names {
+            match self.products.iter().find(|p| &p.name == name) {
+                Some(product) => lines.push(StockTakeLine {
+                    product_name: product.name.clone(),
+                    expected_quantity: product.quantity,
+                    counted_quantity: None,
+                    unit_cost: product.price,
+                }),
+                None => return Err(InventoryError::ProductNotFound(name.clone())),
+            }
+        }
+
+        let id = self.stock_takes.len() as u32 + 1;
+        self.stock_takes.push(StockTake {
+            id,
+            started_at: self.clock.now(),
+            lines,
+            posted: false,
+        });
+        Ok(id)
+    }
+
+    fn start_category_stock_take(&mut self, category: &str) -> Result<u32, InventoryError> {
+        let product_names: Vec<String> = self
+            .products
+            .iter()
+            .filter(|p| p.category == category)
+            .map(|p| p.name.clone())
+            .collect();
+        self.start_stock_take(&product_names)
+    }
+
+    fn start_class_stock_take(&mut self, class: AbcClass) -> Result<u32, InventoryError> {
+        let product_names = self.products_in_class(class);
+        self.start_stock_take(&product_names)
+    }
+
+    fn record_count(&mut self, stock_take_id: u32, product_name: &str, counted_quantity: u32) -> Result<(), InventoryError> {
+        let stock_take = self.open_stock_take(stock_take_id)?;
+        match stock_take.lines.iter_mut().find(|l| l.product_name == product_name) {
+            Some(line) => {
+                line.counted_quantity = Some(counted_quantity);
+                Ok(())
+            }
+            None => Err(InventoryError::StockTakeFailed(format!(
+                "'{}' is not part of stock take #{}",
+                product_name, stock_take_id
+            ))),
+        }
+    }
+
+    fn post_stock_take(&mut self, stock_take_id: u32, user: &str) -> Result<(), InventoryError> {
+        let stock_take = self.open_stock_take(stock_take_id)?;
+        let variances: Vec<(String, i64)> = stock_take
+            .lines
+            .iter()
+            .filter_map(|l| l.variance().map(|v| (l.product_name.clone(), v)))
+            .filter(|(_, v)| *v != 0)
+            .collect();
+
+        // Check every line first so a count is either posted in full or not at all.
+        for (name, variance) in &variances {
+            match self.products.iter().find(|p| &p.name == name) {
+                Some(product) if product.quantity as i64 + variance < 0 => {
+                    return Err(InventoryError::OutOfStock(name.clone()))
+                }
+                Some(_) => {}
+                None => return Err(InventoryError::ProductNotFound(name.clone())),
+            }
+        }
+
+        for (name, variance) in variances {
+            self.adjust_stock(&name, variance, AdjustmentReason::CountCorrection, user)?;
+        }
+        self.open_stock_take(stock_take_id)?.posted = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+
+    fn inventory_with_products() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_product("Mug".to_string(), "Ceramic mug".to_string(), 5.0, 20).unwrap();
+        inventory.add_product("Plate".to_string(), "Dinner plate".to_string(), 8.0, 10).unwrap();
+        inventory.add_product("Pen".to_string(), "Ballpoint pen".to_string(), 1.0, 100).unwrap();
+        inventory.set_product_category("Mug", "Kitchen".to_string()).unwrap();
+        inventory.set_product_category("Plate", "Kitchen".to_string()).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_start_category_stock_take() {
+        let mut inventory = inventory_with_products();
+
+        let id = inventory.start_category_stock_take("Kitchen").unwrap();
+        let stock_take = &inventory.stock_takes[0];
+        assert_eq!(stock_take.id, id);
+        assert_eq!(stock_take.lines.len(), 2);
+        assert_eq!(stock_take.lines[0].expected_quantity, 20);
+
+        let result = inventory.start_category_stock_take("Garden");
+        assert_eq!(result, Err(InventoryError::StockTakeFailed("No products selected for counting".to_string())));
+    }
+
+    #[test]
+    fn test_start_class_stock_take() {
+        let mut inventory = inventory_with_products();
+        inventory.products[2].abc_class = Some(AbcClass::A);
+
+        inventory.start_class_stock_take(AbcClass::A).unwrap();
+        assert_eq!(inventory.stock_takes[0].lines.len(), 1);
+        assert_eq!(inventory.stock_takes[0].lines[0].product_name, "Pen");
+        assert!(inventory.start_class_stock_take(AbcClass::B).is_err());
+    }
+
+    #[test]
+    fn test_record_count_and_variance() {
+        let mut inventory = inventory_with_products();
+        let id = inventory.start_category_stock_take("Kitchen").unwrap();
+
+        inventory.record_count(id, "Mug", 18).unwrap();
+        inventory.record_count(id, "Plate", 9).unwrap();
+        inventory.record_count(id, "Plate", 11).unwrap(); // a recount replaces the earlier figure
+
+        let lines = &inventory.stock_takes[0].lines;
+        assert_eq!(lines[0].variance(), Some(-2));
+        assert_eq!(lines[0].variance_value(), -10.0);
+        assert_eq!(lines[1].variance(), Some(1));
+
+        let result = inventory.record_count(id, "Pen", 100);
+        assert_eq!(
+            result,
+            Err(InventoryError::StockTakeFailed("'Pen' is not part of stock take #1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_post_stock_take() {
+        let mut inventory = inventory_with_products();
+        let id = inventory.start_category_stock_take("Kitchen").unwrap();
+        inventory.record_count(id, "Mug", 18).unwrap();
+
+        // Sales made while counting are kept; only the variance is applied.
+        inventory.record_sale("Mug", 5, 6.0).unwrap();
+        inventory.post_stock_take(id, "manager").unwrap();
+
+        assert_eq!(inventory.products[0].quantity, 13);
+        assert_eq!(inventory.products[1].quantity, 10); // uncounted lines are left alone
+        assert_eq!(inventory.adjustments.len(), 1);
+        assert_eq!(inventory.adjustments[0].reason, AdjustmentReason::CountCorrection);
+
+        let result = inventory.post_stock_take(id, "manager");
+        assert_eq!(
+            result,
+            Err(InventoryError::StockTakeFailed("Stock take #1 has already been posted".to_string()))
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::inventory::{Inventory, InventoryError};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+impl Inventory {
+    /// Reads an inventory saved with `save`. A missing file gives an empty
+    /// inventory, so the first command creates it.
+    pub fn load(path: &Path) -> Result<Inventory, InventoryError> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| InventoryError::StorageFailed(format!("{} is not a valid data file: {}", path.display(), e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Inventory::new()),
+            Err(e) => Err(InventoryError::StorageFailed(format!("cannot read {}: {}", path.display(), e))),
+        }
+    }
+
+    /// Captures everything recorded, but not the clock, so a failed change
+    /// can be undone with `restore`.
+    pub fn snapshot(&self) -> Result<String, InventoryError> {
+        serde_json::to_string(self).map_err(|e| InventoryError::StorageFailed(format!("cannot take a snapshot: {}", e)))
+    }
+
+    pub fn restore(&mut self, snapshot: &str) -> Result<(), InventoryError> {
+        let mut restored: Inventory = serde_json::from_str(snapshot)
+            .map_err(|e| InventoryError::StorageFailed(format!("cannot restore a snapshot: {}", e)))?;
+        std::mem::swap(&mut restored.clock, &mut self.clock);
+        *self = restored;
+        Ok(())
+    }
+
+    // Writes to a temporary file first so an interrupted save never leaves a
+    // half-written data file behind.
+    pub fn save(&self, path: &Path) -> Result<(), InventoryError> {
+        let failed = |e: &dyn std::fmt::Display| InventoryError::StorageFailed(format!("cannot write {}: {}", path.display(), e));
+        let json = serde_json::to_string_pretty(self).map_err(|e| failed(&e))?;
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, json).map_err(|e| failed(&e))?;
+        fs::rename(&temporary, path).map_err(|e| failed(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryManagement;
+    use crate::sales::SalesManagement;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("inventory-storage-{}.json", std::process::id()));
+        let mut inventory = Inventory::new();
+        inventory.add_product("Lamp".to_string(), "Desk lamp".to_string(), 20.0, 5).unwrap();
+        inventory.set_product_sku("Lamp", "LMP-1".to_string()).unwrap();
+        inventory.record_sale("Lamp", 2, 25.0).unwrap();
+
+        inventory.save(&path).unwrap();
+        let loaded = Inventory::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.products[0].sku, "LMP-1");
+        assert_eq!(loaded.products[0].quantity, 3);
+        assert_eq!(loaded.sales[0].total_sale, 50.0);
+        assert_eq!(loaded.sales[0].timestamp, inventory.sales[0].timestamp);
+    }
+
+    #[test]
+    fn test_load_missing_and_invalid_files() {
+        let path = std::env::temp_dir().join(format!("inventory-storage-missing-{}.json", std::process::id()));
+        assert!(Inventory::load(&path).unwrap().products.is_empty());
+
+        fs::write(&path, "not json").unwrap();
+        let result = Inventory::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(InventoryError::StorageFailed(_))));
+    }
+}
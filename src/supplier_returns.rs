@@ -0,0 +1,161 @@
+use crate::inventory::{Inventory, InventoryError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Goods sent back against a purchase. Every return issues a credit note,
+/// identified by `id`, that stays outstanding until the supplier settles it.
+#[derive(Serialize, Deserialize)]
+pub struct SupplierReturn {
+    pub id: u32,
+    pub purchase_id: u32,
+    pub supplier: String,
+    pub product_name: String,
+    pub quantity_returned: u32,
+    pub credit_amount: f64,
+    pub reason: String,
+    pub credit_settled: bool,
+    pub timestamp: NaiveDateTime,
+}
+
+#[allow(dead_code)]
+pub trait SupplierReturnManagement {
+    fn return_to_supplier(&mut self, purchase_id: u32, quantity_returned: u32, reason: &str) -> Result<(), InventoryError>;
+    fn settle_credit_note(&mut self, credit_note_id: u32) -> Result<(), InventoryError>;
+    fn outstanding_credit(&self, supplier: &str) -> f64;
+}
+
+impl SupplierReturnManagement for Inventory {
+    fn return_to_supplier(&mut self, purchase_id: u32, quantity_returned: u32, reason: &str) -> Result<(), InventoryError> {
+        if quantity_returned == 0 {
+            return Err(InventoryError::InvalidQuantity(quantity_returned));
+        }
+
+        let purchase = match self.purchases.iter().find(|p| p.id == purchase_id) {
+            Some(purchase) => purchase,
+            None => {
+                return Err(InventoryError::SupplierReturnFailed(format!("Purchase #{} not found", purchase_id)))
+            }
+        };
+
+        let already_returned: u32 = self
+            .supplier_returns
+            .iter()
+            .filter(|r| r.purchase_id == purchase_id)
+            .map(|r| r.quantity_returned)
+            .sum();
+        if already_returned + quantity_returned > purchase.quantity_purchased {
+            return Err(InventoryError::SupplierReturnFailed(format!(
+                "Only {} unit(s) of purchase #{} can still be returned",
+                purchase.quantity_purchased - already_returned,
+                purchase_id
+            )));
+        }
+
+        let product = match self.products.iter_mut().find(|p| p.name == purchase.product_name) {
+            Some(product) => product,
+            None => return Err(InventoryError::ProductNotFound(purchase.product_name.clone())),
+        };
+        if product.quantity < quantity_returned {
+            return Err(InventoryError::OutOfStock(product.name.clone()));
+        }
+        product.quantity -= quantity_returned;
+
+        let supplier_return = SupplierReturn {
+            id: self.supplier_returns.len() as u32 + 1,
+            purchase_id,
+            supplier: purchase.supplier.clone(),
+            product_name: purchase.product_name.clone(),
+            quantity_returned,
+            credit_amount: purchase.purchase_price * quantity_returned as f64,
+            reason: reason.to_string(),
+            credit_settled: false,
+            timestamp: self.clock.now(),
+        };
+        self.supplier_returns.push(supplier_return);
+        Ok(())
+    }
+
+    fn settle_credit_note(&mut self, credit_note_id: u32) -> Result<(), InventoryError> {
+        match self.supplier_returns.iter_mut().find(|r| r.id == credit_note_id) {
+            Some(r) if r.credit_settled => Err(InventoryError::SupplierReturnFailed(format!(
+                "Credit note #{} is already settled",
+                credit_note_id
+            ))),
+            Some(r) => {
+                r.credit_settled = true;
+                Ok(())
+            }
+            None => Err(InventoryError::SupplierReturnFailed(format!(
+                "Credit note #{} not found",
+                credit_note_id
+            ))),
+        }
+    }
+
+    fn outstanding_credit(&self, supplier: &str) -> f64 {
+        self.supplier_returns
+            .iter()
+            .filter(|r| r.supplier == supplier && !r.credit_settled)
+            .map(|r| r.credit_amount)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::purchases::PurchaseManagement;
+
+    fn inventory_with_purchase() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.record_purchase_from("Acme", "Monitor", 10, 200.0).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_return_to_supplier_success() {
+        let mut inventory = inventory_with_purchase();
+
+        let result = inventory.return_to_supplier(1, 3, "Dead pixels");
+        assert!(result.is_ok());
+
+        assert_eq!(inventory.products[0].quantity, 7);
+        assert_eq!(inventory.total_purchase_cost(), 2000.0 - 600.0);
+
+        let supplier_return = inventory.supplier_returns.last().unwrap();
+        assert_eq!(supplier_return.supplier, "Acme");
+        assert_eq!(supplier_return.credit_amount, 600.0);
+        assert_eq!(supplier_return.reason, "Dead pixels");
+        assert_eq!(inventory.outstanding_credit("Acme"), 600.0);
+    }
+
+    #[test]
+    fn test_return_to_supplier_exceeds_purchase() {
+        let mut inventory = inventory_with_purchase();
+
+        inventory.return_to_supplier(1, 8, "Surplus").unwrap();
+        let result = inventory.return_to_supplier(1, 3, "Surplus");
+        assert_eq!(
+            result,
+            Err(InventoryError::SupplierReturnFailed(
+                "Only 2 unit(s) of purchase #1 can still be returned".to_string()
+            ))
+        );
+
+        let result = inventory.return_to_supplier(7, 1, "Surplus");
+        assert_eq!(result, Err(InventoryError::SupplierReturnFailed("Purchase #7 not found".to_string())));
+    }
+
+    #[test]
+    fn test_settle_credit_note() {
+        let mut inventory = inventory_with_purchase();
+        inventory.return_to_supplier(1, 2, "Damaged in transit").unwrap();
+
+        assert!(inventory.settle_credit_note(1).is_ok());
+        assert_eq!(inventory.outstanding_credit("Acme"), 0.0);
+        assert_eq!(
+            inventory.settle_credit_note(1),
+            Err(InventoryError::SupplierReturnFailed("Credit note #1 is already settled".to_string()))
+        );
+    }
+}
@@ -1,12 +1,34 @@
+use crate::abc::{AbcBasis, AbcClass, AbcManagement, AbcThresholds};
+use crate::adjustments::{AdjustmentManagement, AdjustmentReason};
 use crate::auth::{Auth, AuthError};
-use crate::inventory::{Inventory, InventoryManagement, InventoryError};
-use crate::reporting::Reporting;
+use crate::checkout::{Basket, Checkout, Discount};
+use crate::clock::DateRange;
+use crate::forecast::{ForecastMethod, ForecastSettings};
+use crate::history::{History, Operation};
+use crate::import::{FileFormat, ImportManagement, ImportOptions};
+use crate::input;
+use crate::pricing::PriceManagement;
+use crate::inventory::{Inventory, InventoryManagement, InventoryError, Product};
+use crate::render::{ExportFormat, HtmlRenderer, ReportRenderer, TextRenderer};
+use crate::reporting::{Grouping, Report, Reporting};
 use crate::sales::SalesManagement;
+use crate::screen;
+use crate::search::{self, ProductFilter, ProductSearch};
+use crate::stock_take::StockTakeManagement;
 use crate::purchases::PurchaseManagement;
-use std::io::{self, Write};
+use crate::returns::ReturnManagement;
+use crate::supplier_returns::SupplierReturnManagement;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
 use thiserror::Error; // You can use the `thiserror` crate for easier error handling
 
+const PRODUCTS_PER_PAGE: usize = 20;
+
 #[derive(Debug, Error)]
+#[allow(dead_code)]
 pub enum TuiError {
     #[error("IO Error: {0}")]
     Io(#[from] io::Error),
@@ -16,20 +38,33 @@ pub enum TuiError {
 
     #[error("Authentication Error: {0}")]
     Auth(#[from] AuthError), // If you want to propagate authentication errors too
+
+    #[error("Cancelled")]
+    Cancelled,
 }
 
 pub struct Tui {
     auth_system: Auth,
+    current_user: String,
+    history: History,
+    /// The same data file the command line uses, so both see the same inventory.
+    data_path: PathBuf,
 }
 
 impl Tui {
-    pub fn new(auth_system: Auth) -> Self {
-        Tui { auth_system }
+    pub fn new(auth_system: Auth, data_path: PathBuf) -> Self {
+        Tui {
+            auth_system,
+            current_user: String::new(),
+            history: History::default(),
+            data_path,
+        }
     }
 
-    pub fn run(&mut self) -> Result<(), io::Error> {
+    pub fn run(&mut self) -> Result<(), TuiError> {
         let mut authenticated = false;
 
+        println!("Press Esc then Enter at any prompt to cancel.");
         while !authenticated {
             let username = self.prompt_input("Username: ")?;
             let password = self.prompt_input("Password: ")?;
@@ -37,6 +72,7 @@ impl Tui {
             match self.auth_system.authenticate(&username, &password) {
                 Ok(role) => {
                     authenticated = true;
+                    self.current_user = username;
                     println!("Authentication successful! You are logged in as {:?}", role);
                 }
                 Err(AuthError::InvalidCredentials(_)) => {
@@ -48,130 +84,912 @@ impl Tui {
         self.show_main_menu()
     }
 
-    fn prompt_input(&self, prompt: &str) -> Result<String, io::Error> {
-        let mut input = String::new();
-        print!("{}", prompt);
-        io::stdout().flush()?;
-        io::stdin().read_line(&mut input)?;
-        Ok(input.trim().to_string())
+    fn prompt_input(&self, prompt: &str) -> Result<String, TuiError> {
+        input::read_line(&mut io::stdin().lock(), &mut io::stdout(), prompt)
+    }
+
+    fn prompt_parsed<T>(&self, prompt: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, TuiError> {
+        input::ask(&mut io::stdin().lock(), &mut io::stdout(), prompt, parse)
+    }
+
+    fn prompt_text(&self, prompt: &str) -> Result<String, TuiError> {
+        self.prompt_parsed(prompt, input::parse_text)
+    }
+
+    fn prompt_number<T: FromStr>(&self, prompt: &str) -> Result<T, TuiError> {
+        self.prompt_parsed(prompt, input::parse_whole_number)
+    }
+
+    fn prompt_number_or<T: FromStr>(&self, prompt: &str, default: T) -> Result<T, TuiError> {
+        let number = self.prompt_parsed(prompt, |text| input::parse_optional(text, input::parse_whole_number))?;
+        Ok(number.unwrap_or(default))
+    }
+
+    fn prompt_decimal_or(&self, prompt: &str, default: f64) -> Result<f64, TuiError> {
+        let number = self.prompt_parsed(prompt, |text| input::parse_optional(text, input::parse_decimal))?;
+        Ok(number.unwrap_or(default))
+    }
+
+    fn prompt_money(&self, prompt: &str) -> Result<f64, TuiError> {
+        self.prompt_parsed(prompt, input::parse_money)
+    }
+
+    fn prompt_yes_no(&self, prompt: &str) -> Result<bool, TuiError> {
+        self.prompt_parsed(prompt, input::parse_yes_no)
+    }
+
+    fn prompt_choice<T: Display + Copy>(&self, prompt: &str, options: &[T]) -> Result<T, TuiError> {
+        for (i, option) in options.iter().enumerate() {
+            println!("{}. {}", i + 1, option);
+        }
+        let index = self.prompt_parsed(prompt, |text| input::parse_choice(text, options.len()))?;
+        Ok(options[index])
+    }
+
+    // A blank date leaves that end of the range open.
+    fn prompt_date_range(&self) -> Result<DateRange, TuiError> {
+        let all = DateRange::all();
+        let from = self.prompt_parsed("From Date (YYYY-MM-DD, blank for all): ", |text| {
+            input::parse_optional(text, input::parse_date)
+        })?;
+        let to = self.prompt_parsed("To Date (YYYY-MM-DD, blank for all): ", |text| {
+            input::parse_optional(text, input::parse_date)
+        })?;
+        Ok(DateRange::new(from.unwrap_or(all.from), to.unwrap_or(all.to)))
+    }
+
+    fn prompt_abc_class(&self) -> Result<Option<AbcClass>, TuiError> {
+        self.prompt_parsed("ABC Class (A, B or C, blank for all): ", |text| {
+            input::parse_optional(text, |text| {
+                AbcClass::ALL
+                    .into_iter()
+                    .find(|c| c.to_string().eq_ignore_ascii_case(text))
+                    .ok_or_else(|| format!("'{}' is not an ABC class.", text))
+            })
+        })
     }
 
-    fn show_main_menu(&mut self) -> Result<(), io::Error> {
-        let mut inventory = Inventory::new(); // Assuming an initialized inventory
+    fn show_main_menu(&mut self) -> Result<(), TuiError> {
+        let mut inventory = Inventory::load(&self.data_path)?;
+        let mut saved = inventory.snapshot()?;
     
         loop {
+            // Scheduled price changes take effect while the application is running.
+            inventory.apply_due_prices();
             println!("Welcome to the Store Management System");
             println!("1. Manage Inventory");
             println!("2. Record Sale");
             println!("3. Record Purchase");
-            println!("4. Generate Report");
-            println!("5. Exit");
+            println!("4. Record Return");
+            println!("5. Supplier Returns");
+            println!("6. Stock Take");
+            println!("7. Generate Report");
+            println!("8. Full-Screen Mode");
+            println!("9. Checkout (Point of Sale)");
+            println!("10. Undo Last Change");
+            println!("11. Redo");
+            println!("12. Exit");
     
-            let choice = self.prompt_input("Select an option: ")?;
-            match choice.as_str() {
-                "1" => self.manage_inventory(&mut inventory)?,
-                "2" => self.record_sale(&mut inventory)?,
-                "3" => self.record_purchase(&mut inventory)?,
-                "4" => self.generate_report(&inventory)?,
-                "5" => {
+            let choice = match self.prompt_input("Select an option: ") {
+                Ok(choice) => choice,
+                Err(TuiError::Cancelled) => continue,
+                Err(e) => return Err(e),
+            };
+            let result = match choice.as_str() {
+                "1" => self.manage_inventory(&mut inventory),
+                "2" => self.record_sale(&mut inventory),
+                "3" => self.record_purchase(&mut inventory),
+                "4" => self.record_return(&mut inventory),
+                "5" => self.supplier_returns(&mut inventory),
+                "6" => self.stock_take(&mut inventory),
+                "7" => self.generate_report(&inventory),
+                "8" => screen::run(&mut inventory, &self.current_user, &mut self.history).map_err(TuiError::from),
+                "9" => self.checkout(&mut inventory),
+                "10" => self.undo(&mut inventory),
+                "11" => self.redo(&mut inventory),
+                "12" => {
                     println!("Exiting...");
                     break;
                 }
-                _ => println!("Invalid choice, please try again."),
+                _ => {
+                    println!("Invalid choice, please try again.");
+                    Ok(())
+                }
+            };
+            // Cancelling drops whatever was being entered and returns here.
+            match result {
+                Err(TuiError::Cancelled) => println!("Cancelled."),
+                other => other?,
+            }
+            // Every change is saved straight away, so nothing is lost if the
+            // program is closed without choosing Exit.
+            let current = inventory.snapshot()?;
+            if current != saved {
+                match inventory.save(&self.data_path) {
+                    Ok(()) => saved = current,
+                    Err(e) => println!("Error saving changes: {}", e),
+                }
             }
         }
         Ok(())
     }
 
-    fn manage_inventory(&mut self, inventory: &mut Inventory) -> Result<(), io::Error> {
+    fn manage_inventory(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
         println!("--- Manage Inventory ---");
         println!("1. Add Product");
         println!("2. Edit Product");
         println!("3. Delete Product");
-        println!("4. List Products");
-        println!("5. Back to Main Menu");
+        println!("4. Find Products");
+        println!("5. Adjust Stock");
+        println!("6. Import Products from CSV");
+        println!("7. Import Sales or Purchase History");
+        println!("8. Classify Products (ABC)");
+        println!("9. Restore Archived Product");
+        println!("10. Schedule Price Change or Sale Price");
+        println!("11. Back to Main Menu");
     
         let choice = self.prompt_input("Select an option: ")?;
         match choice.as_str() {
             "1" => {
-                let name = self.prompt_input("Product Name: ")?;
+                let name = self.prompt_text("Product Name: ")?;
                 let description = self.prompt_input("Product Description: ")?;
-                let price: f64 = self.prompt_input("Product Price: ")?.parse().unwrap();
-                let quantity: u32 = self.prompt_input("Product Quantity: ")?.parse().unwrap();
+                let price = self.prompt_money("Product Price: ")?;
+                let quantity: u32 = self.prompt_number("Product Quantity: ")?;
+                let category = self.prompt_input("Product Category: ")?;
+                let sku = self.prompt_input("SKU / Barcode (blank for none): ")?;
+                let reorder_level = self.prompt_number_or("Reorder Level (blank for none): ", 0)?;
 
-                match inventory.add_product(name, description, price, quantity){
-                    Ok(()) => println!("Product added successfully."),
+                match inventory
+                    .check_sku_available(&name, &sku)
+                    .and_then(|()| inventory.add_product(name.clone(), description, price, quantity))
+                    .and_then(|()| inventory.set_product_category(&name, category))
+                    .and_then(|()| inventory.set_product_sku(&name, sku))
+                    .and_then(|()| inventory.set_reorder_level(&name, reorder_level))
+                {
+                    Ok(()) => {
+                        if let Some(product) = inventory.products.iter().find(|p| p.name == name) {
+                            self.history.record(Operation::AddProduct { product: product.clone(), price_changes: Vec::new() });
+                        }
+                        println!("Product added successfully.")
+                    }
                     Err(e) => eprintln!("Failed to add product: {}", e),
                 }
             },
-            "2" => {
-                let product_name = self.prompt_input("Product Name to Edit: ")?;
-                let new_name = self.prompt_input("New name: ").ok();
-                let new_description = self.prompt_input("New description: ").ok();
-                let new_price = self.prompt_input("New Price: ")?.parse::<f64>().ok();
-                let new_quantity = self.prompt_input("New Quantity: ")?.parse::<u32>().ok();
-
-                match inventory.edit_product(
-                    &product_name,
-                    new_name,
-                    new_description,
-                    new_price,
-                    new_quantity
-                ){
-                    Ok(()) => println!("Product removed successfully."),
-                    Err(e) => eprintln!("Failed to remove product: {}", e),
-                }
-            },
+            "2" => self.edit_product(inventory)?,
             "3" => {
                 let product_name = self.prompt_input("Product Name to Delete: ")?;
+                let deleted = inventory
+                    .products
+                    .iter()
+                    .position(|p| p.name == product_name)
+                    .map(|index| Operation::DeleteProduct {
+                        index,
+                        product: inventory.products[index].clone(),
+                        price_changes: inventory
+                            .price_changes
+                            .iter()
+                            .filter(|c| c.product_name == product_name)
+                            .cloned()
+                            .collect(),
+                    });
 
                 match inventory.delete_product(&product_name){
-                    Ok(()) => println!("Product removed successfully."),
+                    Ok(()) => {
+                        self.history.record(deleted.expect("a deleted product existed"));
+                        println!("Product removed successfully.")
+                    }
+                    Err(e @ InventoryError::ProductHasTransactions(_)) => {
+                        println!("{}.", e);
+                        if self.prompt_yes_no("Archive it instead? (y/n): ")? {
+                            self.set_archived(inventory, &product_name, true);
+                        }
+                    }
                     Err(e) => eprintln!("Failed to remove product: {}", e),
                 }
             },
-            "4" => inventory.list_products(),
+            "4" => self.find_products(inventory)?,
+            "5" => {
+                let product_name = self.prompt_input("Product Name to Adjust: ")?;
+                let quantity_change: i64 = self.prompt_number("Quantity Change (e.g. -2 or 3): ")?;
+                let reason = self.prompt_choice("Reason: ", &AdjustmentReason::ALL)?;
+
+                match inventory.adjust_stock(&product_name, quantity_change, reason, &self.current_user) {
+                    Ok(()) => {
+                        self.history.record(Operation::Adjustment(inventory.adjustments[inventory.adjustments.len() - 1].clone()));
+                        println!("Stock adjusted successfully.")
+                    }
+                    Err(e) => eprintln!("Failed to adjust stock: {}", e),
+                }
+            },
+            "6" => self.import_products(inventory)?,
+            "7" => self.import_history(inventory)?,
+            "8" => self.classify_products(inventory)?,
+            "9" => {
+                let archived: Vec<&str> = inventory.products.iter().filter(|p| p.archived).map(|p| p.name.as_str()).collect();
+                if archived.is_empty() {
+                    println!("No products are archived.");
+                } else {
+                    println!("Archived: {}", archived.join(", "));
+                    let product_name = self.prompt_text("Product Name to Restore: ")?;
+                    self.set_archived(inventory, &product_name, false);
+                }
+            }
+            "10" => self.schedule_price(inventory)?,
+            "11" => {}
             _ => println!("Invalid choice, returning to main menu."),
         }
         Ok(())
     }
 
-    fn record_sale(&mut self, inventory: &mut Inventory) -> Result<(), io::Error> {
-        let product_name = self.prompt_input("Product Name: ")?;
-        let quantity: u32 = self.prompt_input("Quantity Sold: ")?.parse().unwrap();
-        let sale_price: f64 = self.prompt_input("Sale Price: ")?.parse().unwrap();
-        
-        match inventory.record_sale(&product_name, quantity, sale_price) {
-            Ok(_) => println!("Sale recorded successfully."),
+    // Dates are whole days: a sale price runs to the end of its last day.
+    fn schedule_price(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let product_name = self.prompt_text("Product Name: ")?;
+        let price = self.prompt_money("New Price: ")?;
+        let from = self.prompt_parsed("Starts On (YYYY-MM-DD): ", input::parse_date)?;
+        let to = self.prompt_parsed("Last Day of a Temporary Sale Price (blank for a permanent change): ", |text| {
+            input::parse_optional(text, input::parse_date)
+        })?;
+
+        let until = to.and_then(|date| date.succ_opt()).map(|date| date.and_time(Default::default()));
+        match inventory.schedule_price(&product_name, price, from.and_time(Default::default()), until, &self.current_user) {
+            Ok(()) => println!("Price scheduled successfully."),
+            Err(e) => println!("Error scheduling price: {}", e),
+        }
+        Ok(())
+    }
+
+    // Archiving is undone like any other edit of the product.
+    fn set_archived(&mut self, inventory: &mut Inventory, product_name: &str, archived: bool) {
+        let result = if archived {
+            inventory.archive_product(product_name)
+        } else {
+            inventory.restore_product(product_name)
+        };
+        match result {
+            Ok(()) => {
+                let after = inventory.products.iter().find(|p| p.name == product_name).cloned().expect("the product exists");
+                let before = Product { archived: !archived, ..after.clone() };
+                self.history.record(Operation::EditProduct { before, after });
+                println!("Product {}.", if archived { "archived" } else { "restored" });
+            }
+            Err(e) => eprintln!("Failed to update product: {}", e),
+        }
+    }
+
+    // Each prompt shows the current value in brackets; a blank answer keeps it.
+    fn edit_product(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let product_name = self.prompt_text("Product Name to Edit: ")?;
+        let product = match inventory.products.iter().find(|p| p.name == product_name) {
+            Some(product) => product,
+            None => {
+                eprintln!("Failed to update product: {}", InventoryError::ProductNotFound(product_name));
+                return Ok(());
+            }
+        };
+        let before = product.clone();
+        let keep_blank = |text: &str| Ok(Some(text.to_string()).filter(|t| !t.is_empty()));
+
+        let new_name = self.prompt_parsed(&format!("Name [{}]: ", product.name), keep_blank)?;
+        let new_description = self.prompt_parsed(&format!("Description [{}]: ", product.description), keep_blank)?;
+        let new_price = self.prompt_parsed(&format!("Price [{:.2}]: ", product.price), |text| {
+            input::parse_optional(text, input::parse_money)
+        })?;
+        let new_quantity = self.prompt_parsed(&format!("Quantity [{}]: ", product.quantity), |text| {
+            input::parse_optional(text, input::parse_whole_number)
+        })?;
+        let new_category = self.prompt_parsed(&format!("Category [{}]: ", product.category), keep_blank)?;
+        let new_sku = self.prompt_parsed(&format!("SKU [{}]: ", product.sku), keep_blank)?;
+        let new_reorder_level = self.prompt_parsed(&format!("Reorder Level [{}]: ", product.reorder_level), |text| {
+            input::parse_optional(text, input::parse_whole_number)
+        })?;
+
+        let name = new_name.clone().unwrap_or_else(|| product_name.clone());
+        let result = inventory
+            .check_sku_available(&product_name, new_sku.as_deref().unwrap_or(""))
+            .and_then(|()| {
+                inventory.edit_product(&product_name, new_name, new_description, new_price, new_quantity, &self.current_user)
+            })
+            .and_then(|()| match new_category {
+                Some(category) => inventory.set_product_category(&name, category),
+                None => Ok(()),
+            })
+            .and_then(|()| match new_sku {
+                Some(sku) => inventory.set_product_sku(&name, sku),
+                None => Ok(()),
+            })
+            .and_then(|()| match new_reorder_level {
+                Some(level) => inventory.set_reorder_level(&name, level),
+                None => Ok(()),
+            });
+        match result {
+            Ok(()) => {
+                if let Some(after) = inventory.products.iter().find(|p| p.name == name) {
+                    self.history.record(Operation::EditProduct { before, after: after.clone() });
+                }
+                println!("Product updated successfully.")
+            }
+            Err(e) => eprintln!("Failed to update product: {}", e),
+        }
+        Ok(())
+    }
+
+    // Shows matching products a page at a time. Typing anything other than a
+    // paging command searches again with the new text, keeping the other filters.
+    fn find_products(&mut self, inventory: &Inventory) -> Result<(), TuiError> {
+        let mut filter = ProductFilter {
+            query: self.prompt_input("Search name, SKU or description (blank for all): ")?,
+            ..ProductFilter::default()
+        };
+        if self.prompt_yes_no("Set filters? (y/n): ")? {
+            filter.in_stock_only = self.prompt_yes_no("In stock only? (y/n): ")?;
+            filter.below_reorder_level = self.prompt_yes_no("Below reorder level only? (y/n): ")?;
+            filter.include_archived = self.prompt_yes_no("Include archived products? (y/n): ")?;
+            filter.category = Some(self.prompt_input("Category (blank for all): ")?).filter(|c| !c.is_empty());
+            filter.min_price = self.prompt_parsed("Minimum Price (blank for none): ", |text| {
+                input::parse_optional(text, input::parse_money)
+            })?;
+            filter.max_price = self.prompt_parsed("Maximum Price (blank for none): ", |text| {
+                input::parse_optional(text, input::parse_money)
+            })?;
+        }
+
+        let mut page = 0;
+        loop {
+            let matches = inventory.search_products(&filter);
+            let (rows, total_pages) = search::paginate(&matches, page, PRODUCTS_PER_PAGE);
+            println!("--- {} product(s), page {} of {} ---", matches.len(), page + 1, total_pages);
+            for product in rows {
+                println!(
+                    "{:<30} {:<14} {:<16} {:>8} {:>10.2}{}",
+                    product.name,
+                    product.sku,
+                    product.category,
+                    product.quantity,
+                    product.price,
+                    if product.archived {
+                        "  (archived)"
+                    } else if product.is_below_reorder_level() {
+                        "  (reorder)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+
+            let command = self.prompt_input("n next, p previous, text to search again, blank to finish: ")?;
+            match command.as_str() {
+                "" => return Ok(()),
+                "n" if page + 1 < total_pages => page += 1,
+                "p" => page = page.saturating_sub(1),
+                "n" => println!("Already on the last page."),
+                _ => {
+                    filter.query = command;
+                    page = 0;
+                }
+            }
+        }
+    }
+
+    fn classify_products(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let range = self.prompt_date_range()?;
+        let basis = match self.prompt_input("Rank by (1) Revenue or (2) Profit: ")?.as_str() {
+            "2" => AbcBasis::Profit,
+            _ => AbcBasis::Revenue,
+        };
+        let defaults = AbcThresholds::default();
+        let thresholds = AbcThresholds {
+            a_percent: self.prompt_decimal_or("Class A cumulative % (blank for 80): ", defaults.a_percent)?,
+            b_percent: self.prompt_decimal_or("Class B cumulative % (blank for 95): ", defaults.b_percent)?,
+        };
+
+        match inventory.classify_products(&range, basis, thresholds) {
+            Ok(()) => {
+                for class in AbcClass::ALL {
+                    println!("Class {}: {} product(s)", class, inventory.products_in_class(class).len());
+                }
+            }
+            Err(e) => println!("Error classifying products: {}", e),
+        }
+        Ok(())
+    }
+
+    fn import_products(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let path = self.prompt_input("CSV File Path: ")?;
+        let mut options = ImportOptions {
+            dry_run: self.prompt_yes_no("Dry run only? (y/n): ")?,
+            upsert: self.prompt_yes_no("Update existing products? (y/n): ")?,
+            user: self.current_user.clone(),
+            ..ImportOptions::default()
+        };
+        let mapping = self.prompt_input("Column mapping, e.g. name=Item,price=Retail (blank if headers match): ")?;
+        for pair in mapping.split(',').filter(|p| !p.trim().is_empty()) {
+            match pair.split_once('=') {
+                Some((field, header)) => {
+                    options.column_map.insert(field.trim().to_lowercase(), header.trim().to_string());
+                }
+                None => println!("Ignoring mapping '{}', expected field=Header.", pair),
+            }
+        }
+
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Error opening {}: {}", path, e);
+                return Ok(());
+            }
+        };
+        match inventory.import_products(file, &options) {
+            Ok(summary) => {
+                for row in &summary.errors {
+                    println!("Line {}: {}", row.line, row.error);
+                }
+                let verb = if options.dry_run { "would be" } else { "were" };
+                println!(
+                    "{} product(s) {} added, {} {} updated, {} row(s) rejected.",
+                    summary.added,
+                    verb,
+                    summary.updated,
+                    verb,
+                    summary.errors.len()
+                );
+            }
+            Err(e) => println!("Error importing products: {}", e),
+        }
+        Ok(())
+    }
+
+    fn import_history(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let kind = self.prompt_input("Import (1) Sales or (2) Purchases: ")?;
+        let path = self.prompt_input("CSV or JSON File Path: ")?;
+        let options = ImportOptions {
+            dry_run: self.prompt_yes_no("Dry run only? (y/n): ")?,
+            update_stock: self.prompt_yes_no("Apply to current stock levels? (y/n): ")?,
+            ..ImportOptions::default()
+        };
+
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Error opening {}: {}", path, e);
+                return Ok(());
+            }
+        };
+        let format = FileFormat::from_path(&path);
+        let result = match kind.as_str() {
+            "1" => inventory.import_sales(file, format, &options),
+            "2" => inventory.import_purchases(file, format, &options),
+            _ => {
+                println!("Invalid choice, returning to main menu.");
+                return Ok(());
+            }
+        };
+        match result {
+            Ok(summary) => {
+                for row in &summary.errors {
+                    println!("Row {}: {}", row.line, row.error);
+                }
+                println!(
+                    "{} transaction(s) {} imported, {} row(s) rejected.",
+                    summary.added,
+                    if options.dry_run { "would be" } else { "were" },
+                    summary.errors.len()
+                );
+            }
+            Err(e) => println!("Error importing history: {}", e),
+        }
+        Ok(())
+    }
+
+    fn record_sale(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let product_name = self.prompt_text("Product Name: ")?;
+        let quantity: u32 = self.prompt_number("Quantity Sold: ")?;
+        let sale_price = self.prompt_parsed("Sale Price (blank for the current price): ", |text| {
+            input::parse_optional(text, input::parse_money)
+        })?;
+
+        let result = match sale_price {
+            Some(sale_price) => inventory.record_sale(&product_name, quantity, sale_price),
+            None => inventory.record_sale_at_current_price(&product_name, quantity).map(|_| ()),
+        };
+        match result {
+            Ok(_) => {
+                self.history.record(Operation::Sale(inventory.sales[inventory.sales.len() - 1..].to_vec()));
+                println!("Sale recorded successfully.")
+            }
             Err(e) => println!("Error recording sale: {}", e),
         }
         Ok(())
     }
     
-    fn record_purchase(&mut self, inventory: &mut Inventory) -> Result<(), io::Error> {
-        let product_name = self.prompt_input("Product Name: ")?;
-        let quantity: u32 = self.prompt_input("Quantity Purchased: ")?.parse().unwrap();
-        let purchase_price: f64 = self.prompt_input("Purchase Price: ")?.parse().unwrap();
+    // One line per command: a code scans a product (prefix "3*" for several),
+    // and the letters below edit the basket or finish the sale.
+    fn checkout(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        println!("--- Checkout ---");
+        println!("Scan or type an SKU, barcode or product name; 3*CODE adds three.");
+        println!("q LINE QTY: change quantity    p LINE PRICE: change price    r LINE: remove line");
+        println!("d 10% or d 5.00: discount      t: take payment               x: abandon sale");
+        let mut basket = Basket::default();
+
+        loop {
+            let command = self.prompt_input("> ")?;
+            let mut words = command.split_whitespace();
+            let line_number = |word: Option<&str>| {
+                word.and_then(|w| w.parse::<usize>().ok())
+                    .filter(|&n| n > 0)
+                    .map(|n| n - 1)
+                    .ok_or_else(|| InventoryError::InvalidInput("give the line number shown in the basket".to_string()))
+            };
+            let result = match words.next() {
+                None => continue,
+                Some("x") => {
+                    println!("Sale abandoned.");
+                    return Ok(());
+                }
+                Some("t") => {
+                    if basket.lines.is_empty() {
+                        println!("The basket is empty.");
+                        continue;
+                    }
+                    let total = basket.total();
+                    println!("Total Due: ${:.2}", total);
+                    let tendered = self.prompt_parsed("Amount Tendered: ", |text| match input::parse_money(text)? {
+                        amount if amount < total => Err(format!("At least ${:.2} is due.", total)),
+                        amount => Ok(amount),
+                    })?;
+                    match inventory.complete_checkout(&basket, tendered) {
+                        Ok(receipt) => {
+                            let sales = inventory.sales.iter().filter(|s| s.receipt_id == receipt.receipt_id).cloned().collect();
+                            self.history.record(Operation::Sale(sales));
+                            println!(
+                                "Receipt #{}: total ${:.2}, tendered ${:.2}, change ${:.2}",
+                                receipt.receipt_id, receipt.total, receipt.tendered, receipt.change
+                            );
+                            return Ok(());
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                Some("q") => line_number(words.next()).and_then(|line| {
+                    let quantity = words.next().and_then(|w| input::parse_whole_number(w).ok());
+                    basket.set_quantity(inventory, line, quantity.unwrap_or(0))
+                }),
+                Some("p") => line_number(words.next()).and_then(|line| {
+                    match words.next().map(input::parse_money) {
+                        Some(Ok(price)) => basket.set_price(line, price),
+                        _ => Err(InventoryError::InvalidInput("give the new unit price".to_string())),
+                    }
+                }),
+                Some("r") => line_number(words.next()).and_then(|line| basket.remove(line)).map(|_| ()),
+                Some("d") => {
+                    let discount = match words.next() {
+                        None => Ok(Discount::None),
+                        Some(word) => match word.strip_suffix('%') {
+                            Some(percent) => input::parse_decimal(percent).map(Discount::Percent),
+                            None => input::parse_money(word).map(Discount::Amount),
+                        },
+                    };
+                    discount
+                        .map_err(InventoryError::InvalidInput)
+                        .and_then(|discount| basket.set_discount(discount))
+                }
+                Some(_) => {
+                    let (quantity, code) = match command.split_once('*') {
+                        Some((quantity, code)) => (input::parse_whole_number(quantity.trim()).unwrap_or(0), code.trim()),
+                        None => (1, command.as_str()),
+                    };
+                    basket.add(inventory, code, quantity).map(|_| ())
+                }
+            };
+            if let Err(e) = result {
+                println!("{}", e);
+            }
+
+            for (i, line) in basket.lines.iter().enumerate() {
+                println!(
+                    "{:>3}. {:<30} {:>5} x {:>9.2} = {:>10.2}",
+                    i + 1,
+                    line.product_name,
+                    line.quantity,
+                    line.unit_price,
+                    line.total()
+                );
+            }
+            if basket.discount_amount() > 0.0 {
+                println!("     Subtotal ${:.2}, discount -${:.2}", basket.subtotal(), basket.discount_amount());
+            }
+            println!("     Total ${:.2}", basket.total());
+        }
+    }
+
+    fn record_purchase(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let supplier = self.prompt_input("Supplier: ")?;
+        let product_name = self.prompt_text("Product Name: ")?;
+        let quantity: u32 = self.prompt_number("Quantity Purchased: ")?;
+        let purchase_price = self.prompt_money("Purchase Price: ")?;
         
-        match inventory.record_purchase(&product_name, quantity, purchase_price) {
-            Ok(_) => println!("Purchase recorded successfully."),
+        match inventory.record_purchase_from(&supplier, &product_name, quantity, purchase_price) {
+            Ok(_) => {
+                self.history.record(Operation::Purchase(inventory.purchases[inventory.purchases.len() - 1].clone()));
+                println!("Purchase recorded successfully.")
+            }
             Err(e) => println!("Error recording purchase: {}", e),
         }
         Ok(())
     }
 
-    fn generate_report(&self, inventory: &Inventory) -> Result<(), io::Error> {
+    // Shows exactly what will be reverted and asks before changing anything.
+    fn undo(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let summary = match self.history.next_undo() {
+            Some(operation) => operation.undo_summary(),
+            None => {
+                println!("Nothing to undo.");
+                return Ok(());
+            }
+        };
+        println!("{}", summary);
+        if self.prompt_yes_no("Undo this change? (y/n): ")? {
+            match self.history.undo(inventory, &self.current_user) {
+                Ok(()) => println!("Change undone."),
+                Err(e) => println!("{}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn redo(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let summary = match self.history.next_redo() {
+            Some(operation) => operation.redo_summary(),
+            None => {
+                println!("Nothing to redo.");
+                return Ok(());
+            }
+        };
+        println!("{}", summary);
+        if self.prompt_yes_no("Redo this change? (y/n): ")? {
+            match self.history.redo(inventory, &self.current_user) {
+                Ok(()) => println!("Change redone."),
+                Err(e) => println!("{}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn record_return(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        let sale_id: u32 = self.prompt_number("Sale ID: ")?;
+        let quantity_restocked: u32 = self.prompt_number("Units to Restock: ")?;
+        let quantity_written_off: u32 = self.prompt_number("Units to Write Off: ")?;
+
+        match inventory.record_return(sale_id, quantity_restocked, quantity_written_off) {
+            Ok(_) => println!("Return recorded successfully."),
+            Err(e) => println!("Error recording return: {}", e),
+        }
+        Ok(())
+    }
+
+    fn supplier_returns(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        println!("--- Supplier Returns ---");
+        println!("1. Return Goods to Supplier");
+        println!("2. Settle Credit Note");
+
+        let choice = self.prompt_input("Select an option: ")?;
+        match choice.as_str() {
+            "1" => {
+                let purchase_id: u32 = self.prompt_number("Purchase ID: ")?;
+                let quantity: u32 = self.prompt_number("Quantity Returned: ")?;
+                let reason = self.prompt_input("Reason: ")?;
+
+                match inventory.return_to_supplier(purchase_id, quantity, &reason) {
+                    Ok(_) => println!("Supplier return recorded successfully."),
+                    Err(e) => println!("Error recording supplier return: {}", e),
+                }
+            }
+            "2" => {
+                let credit_note_id: u32 = self.prompt_number("Credit Note ID: ")?;
+
+                match inventory.settle_credit_note(credit_note_id) {
+                    Ok(_) => println!("Credit note settled."),
+                    Err(e) => println!("Error settling credit note: {}", e),
+                }
+            }
+            _ => println!("Invalid choice, returning to main menu."),
+        }
+        Ok(())
+    }
+
+    fn stock_take(&mut self, inventory: &mut Inventory) -> Result<(), TuiError> {
+        println!("--- Stock Take ---");
+        println!("1. Start Count for a Category");
+        println!("2. Start Count for Selected Products");
+        println!("3. Enter Counted Quantities");
+        println!("4. Review Variances");
+        println!("5. Post Count as Adjustments");
+        println!("6. Start Count for an ABC Class");
+
+        let choice = self.prompt_input("Select an option: ")?;
+        match choice.as_str() {
+            "1" => {
+                let category = self.prompt_input("Category: ")?;
+
+                match inventory.start_category_stock_take(&category) {
+                    Ok(id) => println!("Stock take #{} started.", id),
+                    Err(e) => println!("Error starting stock take: {}", e),
+                }
+            }
+            "2" => {
+                let names = self.prompt_input("Product Names (comma separated): ")?;
+                let product_names: Vec<String> = names.split(',').map(|n| n.trim().to_string()).collect();
+
+                match inventory.start_stock_take(&product_names) {
+                    Ok(id) => println!("Stock take #{} started.", id),
+                    Err(e) => println!("Error starting stock take: {}", e),
+                }
+            }
+            "3" => {
+                let stock_take_id: u32 = self.prompt_number("Stock Take ID: ")?;
+                println!("Enter a blank product name to finish.");
+                loop {
+                    let product_name = self.prompt_input("Product Name: ")?;
+                    if product_name.is_empty() {
+                        break;
+                    }
+                    let counted: u32 = self.prompt_number("Counted Quantity: ")?;
+
+                    if let Err(e) = inventory.record_count(stock_take_id, &product_name, counted) {
+                        println!("Error recording count: {}", e);
+                    }
+                }
+            }
+            "4" => {
+                let stock_take_id: u32 = self.prompt_number("Stock Take ID: ")?;
+                match inventory.generate_stock_take_report(stock_take_id) {
+                    Ok(report) => self.output_report(&report)?,
+                    Err(e) => println!("Error reviewing stock take: {}", e),
+                }
+            }
+            "5" => {
+                let stock_take_id: u32 = self.prompt_number("Stock Take ID: ")?;
+
+                match inventory.post_stock_take(stock_take_id, &self.current_user) {
+                    Ok(_) => println!("Stock take posted successfully."),
+                    Err(e) => println!("Error posting stock take: {}", e),
+                }
+            }
+            "6" => match self.prompt_abc_class()? {
+                Some(class) => match inventory.start_class_stock_take(class) {
+                    Ok(id) => println!("Stock take #{} started.", id),
+                    Err(e) => println!("Error starting stock take: {}", e),
+                },
+                None => println!("Invalid class, returning to main menu."),
+            },
+            _ => println!("Invalid choice, returning to main menu."),
+        }
+        Ok(())
+    }
+
+    fn generate_report(&self, inventory: &Inventory) -> Result<(), TuiError> {
         println!("--- Generate Report ---");
         println!("1. Inventory Report");
         println!("2. Sales Report");
         println!("3. Purchase Report");
-        
+        println!("4. Stock Adjustment Report");
+        println!("5. Owner Review (HTML file with inventory, sales and purchases)");
+        println!("6. Profit and Loss");
+        println!("7. Inventory Valuation");
+        println!("8. Top Sellers");
+        println!("9. Slow Movers");
+        println!("10. Dead Stock");
+        println!("11. Demand Forecast and Reorder Suggestions");
+        println!("12. Price History");
+
         let choice = self.prompt_input("Select an option: ")?;
-        match choice.as_str() {
-            "1" => inventory.generate_inventory_report(),
-            "2" => inventory.generate_sales_report(),
-            "3" => inventory.generate_purchase_report(),
-            _ => println!("Invalid choice, returning to main menu."),
+        let report = match choice.as_str() {
+            "1" => inventory.generate_inventory_report(self.prompt_abc_class()?),
+            "2" => inventory.generate_sales_report(&self.prompt_date_range()?),
+            "3" => inventory.generate_purchase_report(&self.prompt_date_range()?),
+            "4" => inventory.generate_adjustment_report(),
+            "5" => return self.export_review(inventory),
+            "6" => {
+                let range = self.prompt_date_range()?;
+                let grouping = match self.prompt_input("Break down by (1) Product or (2) Category: ")?.as_str() {
+                    "2" => Grouping::Category,
+                    _ => Grouping::Product,
+                };
+                inventory.generate_profit_and_loss_report(&range, grouping)
+            }
+            "7" => {
+                let as_of = self
+                    .prompt_parsed("As of Date (YYYY-MM-DD, blank for today): ", |text| {
+                        input::parse_optional(text, input::parse_date)
+                    })?
+                    .unwrap_or_else(|| inventory.clock.now().date());
+                inventory.generate_valuation_report(as_of, self.prompt_abc_class()?)
+            }
+            "8" => {
+                let range = self.prompt_date_range()?;
+                let limit = self.prompt_number_or("Number of Products (blank for 10): ", 10)?;
+                inventory.generate_top_sellers_report(&range, limit)
+            }
+            "9" => {
+                let range = self.prompt_date_range()?;
+                let threshold = self.prompt_decimal_or("Sell-Through Threshold % (blank for 25): ", 25.0)?;
+                inventory.generate_slow_movers_report(&range, threshold, self.prompt_abc_class()?)
+            }
+            "10" => {
+                let days = self.prompt_number_or("Days Without a Sale (blank for 90): ", 90)?;
+                inventory.generate_dead_stock_report(days, self.prompt_abc_class()?)
+            }
+            "11" => match inventory.generate_forecast_report(&self.prompt_forecast_settings()?) {
+                Ok(report) => report,
+                Err(e) => {
+                    println!("Error forecasting demand: {}", e);
+                    return Ok(());
+                }
+            },
+            "12" => {
+                let product_name = self.prompt_input("Product Name (blank for all): ")?;
+                inventory.generate_price_history_report(Some(product_name.as_str()).filter(|name| !name.is_empty()))
+            }
+            _ => {
+                println!("Invalid choice, returning to main menu.");
+                return Ok(());
+            }
+        };
+        self.output_report(&report)
+    }
+
+    fn prompt_forecast_settings(&self) -> Result<ForecastSettings, TuiError> {
+        println!("1. Moving Average");
+        println!("2. Exponential Smoothing");
+        println!("3. Seasonal Naive");
+        let method = match self.prompt_input("Forecast Method: ")?.as_str() {
+            "2" => ForecastMethod::ExponentialSmoothing(self.prompt_decimal_or("Smoothing Factor (blank for 0.3): ", 0.3)?),
+            "3" => ForecastMethod::SeasonalNaive(self.prompt_number_or("Season Length in Days (blank for 7): ", 7)?),
+            _ => ForecastMethod::MovingAverage(self.prompt_number_or("Average Over Days (blank for 7): ", 7)?),
+        };
+        let defaults = ForecastSettings::default();
+        Ok(ForecastSettings {
+            method,
+            history_days: self.prompt_number_or("Days of History (blank for 56): ", defaults.history_days)?,
+            horizon_days: self.prompt_number_or("Days to Cover, Including Lead Time (blank for 14): ", defaults.horizon_days)?,
+        })
+    }
+
+    fn export_review(&self, inventory: &Inventory) -> Result<(), TuiError> {
+        let range = self.prompt_date_range()?;
+        let path = self.prompt_input("File Path (e.g. review.html): ")?;
+        let reports = [
+            inventory.generate_inventory_report(None),
+            inventory.generate_sales_report(&range),
+            inventory.generate_purchase_report(&range),
+        ];
+
+        let html = HtmlRenderer.render_document(&format!("Store Review ({})", range), &reports);
+        match fs::write(&path, html) {
+            Ok(()) => println!("Review exported to {}.", path),
+            Err(e) => println!("Error exporting review: {}", e),
+        }
+        Ok(())
+    }
+
+    fn output_report(&self, report: &Report) -> Result<(), TuiError> {
+        let format = self.prompt_input("Export as csv, json, md or html (blank to display): ")?;
+        if format.is_empty() {
+            print!("{}", TextRenderer.render(report));
+            return Ok(());
+        }
+
+        let format = match ExportFormat::from_name(&format) {
+            Some(format) => format,
+            None => {
+                println!("Unknown format '{}', displaying instead.", format);
+                print!("{}", TextRenderer.render(report));
+                return Ok(());
+            }
+        };
+        let path = self.prompt_input(&format!("File Path (e.g. report.{}): ", format.extension()))?;
+        match fs::write(&path, format.renderer().render(report)) {
+            Ok(()) => println!("Report exported to {}.", path),
+            Err(e) => println!("Error exporting report: {}", e),
         }
         Ok(())
     }
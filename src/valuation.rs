@@ -0,0 +1,164 @@
+use crate::inventory::Inventory;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Stock levels and unit costs as they stood at the end of a past day,
+/// worked back from today's quantities through every recorded movement.
+pub trait Valuation {
+    fn quantity_at(&self, product_name: &str, date: NaiveDate) -> u32;
+    fn unit_cost_at(&self, product_name: &str, date: NaiveDate) -> f64;
+    fn existed_on(&self, product_name: &str, date: NaiveDate) -> bool;
+}
+
+impl Inventory {
+    /// Net stock change for a product from every movement recorded after `date`.
+    fn movements_after(&self, product_name: &str, date: NaiveDate) -> i64 {
+        let after = |timestamp: &NaiveDateTime| timestamp.date() > date;
+        let purchased: i64 = self
+            .purchases
+            .iter()
+            .filter(|p| p.product_name == product_name && after(&p.timestamp))
+            .map(|p| p.quantity_purchased as i64)
+            .sum();
+        let sold: i64 = self
+            .sales
+            .iter()
+            .filter(|s| s.product_name == product_name && after(&s.timestamp))
+            .map(|s| s.quantity_sold as i64)
+            .sum();
+        let restocked: i64 = self
+            .returns
+            .iter()
+            .filter(|r| r.product_name == product_name && after(&r.timestamp))
+            .map(|r| r.quantity_restocked as i64)
+            .sum();
+        let sent_back: i64 = self
+            .supplier_returns
+            .iter()
+            .filter(|r| r.product_name == product_name && after(&r.timestamp))
+            .map(|r| r.quantity_returned as i64)
+            .sum();
+        let adjusted: i64 = self
+            .adjustments
+            .iter()
+            .filter(|a| a.product_name == product_name && after(&a.timestamp))
+            .map(|a| a.quantity_change)
+            .sum();
+        purchased - sold + restocked - sent_back + adjusted
+    }
+
+    /// Timestamp of the earliest movement recorded for a product, if any.
+    fn first_movement(&self, product_name: &str) -> Option<NaiveDateTime> {
+        let purchases = self.purchases.iter().filter(|p| p.product_name == product_name).map(|p| p.timestamp);
+        let sales = self.sales.iter().filter(|s| s.product_name == product_name).map(|s| s.timestamp);
+        let returns = self.returns.iter().filter(|r| r.product_name == product_name).map(|r| r.timestamp);
+        let sent_back = self
+            .supplier_returns
+            .iter()
+            .filter(|r| r.product_name == product_name)
+            .map(|r| r.timestamp);
+        let adjusted = self.adjustments.iter().filter(|a| a.product_name == product_name).map(|a| a.timestamp);
+        purchases.chain(sales).chain(returns).chain(sent_back).chain(adjusted).min()
+    }
+}
+
+impl Valuation for Inventory {
+    fn quantity_at(&self, product_name: &str, date: NaiveDate) -> u32 {
+        let current = match self.products.iter().find(|p| p.name == product_name) {
+            Some(product) => product.quantity as i64,
+            None => return 0,
+        };
+        // Stock entered without a recorded movement (opening stock, direct edits)
+        // cannot be dated, so the result is never allowed to go below zero.
+        (current - self.movements_after(product_name, date)).max(0) as u32
+    }
+
+    /// Weighted average purchase price up to `date`, or the product price when
+    /// nothing had been bought yet.
+    fn unit_cost_at(&self, product_name: &str, date: NaiveDate) -> f64 {
+        let (quantity, cost) = self
+            .purchases
+            .iter()
+            .filter(|p| p.product_name == product_name && p.timestamp.date() <= date)
+            .fold((0u32, 0.0), |(quantity, cost), p| {
+                (quantity + p.quantity_purchased, cost + p.total_cost)
+            });
+        if quantity > 0 {
+            return cost / quantity as f64;
+        }
+        self.products
+            .iter()
+            .find(|p| p.name == product_name)
+            .map_or(0.0, |p| p.price)
+    }
+
+    /// Whether a product was already on the books at the end of `date`. Imported
+    /// history can predate the product itself, so its earliest recorded movement
+    /// counts as well as the day it was added.
+    fn existed_on(&self, product_name: &str, date: NaiveDate) -> bool {
+        let created = match self.products.iter().find(|p| p.name == product_name) {
+            Some(product) => product.created,
+            None => return false,
+        };
+        let Some(created) = created else {
+            return true;
+        };
+        let first_seen = self.first_movement(product_name).map_or(created, |moved| moved.min(created));
+        first_seen.date() <= date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::inventory::InventoryManagement;
+    use crate::purchases::PurchaseManagement;
+    use crate::sales::SalesManagement;
+
+    fn on(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn inventory_with_history() -> Inventory {
+        let mut inventory = Inventory::with_clock(Box::new(FixedClock(on(1).and_hms_opt(9, 0, 0).unwrap())));
+        inventory.add_product("Lamp".to_string(), "Desk lamp".to_string(), 40.0, 10).unwrap();
+        inventory.clock = Box::new(FixedClock(on(10).and_hms_opt(9, 0, 0).unwrap()));
+        inventory.record_purchase("Lamp", 10, 20.0).unwrap();
+        inventory.clock = Box::new(FixedClock(on(20).and_hms_opt(9, 0, 0).unwrap()));
+        inventory.record_sale("Lamp", 5, 45.0).unwrap();
+        inventory.record_purchase("Lamp", 10, 26.0).unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_quantity_at() {
+        let inventory = inventory_with_history();
+
+        assert_eq!(inventory.products[0].quantity, 25);
+        assert_eq!(inventory.quantity_at("Lamp", on(5)), 10);
+        assert_eq!(inventory.quantity_at("Lamp", on(10)), 20);
+        assert_eq!(inventory.quantity_at("Lamp", on(20)), 25);
+        assert_eq!(inventory.quantity_at("Nothing", on(20)), 0);
+    }
+
+    #[test]
+    fn test_unit_cost_at() {
+        let inventory = inventory_with_history();
+
+        assert_eq!(inventory.unit_cost_at("Lamp", on(5)), 40.0);
+        assert_eq!(inventory.unit_cost_at("Lamp", on(15)), 20.0);
+        assert_eq!(inventory.unit_cost_at("Lamp", on(31)), 23.0);
+    }
+
+    #[test]
+    fn test_existed_on() {
+        let mut inventory = inventory_with_history();
+        inventory.add_product("Bulb".to_string(), "Spare bulb".to_string(), 5.0, 10).unwrap();
+
+        assert!(inventory.existed_on("Lamp", on(1)));
+        assert!(!inventory.existed_on("Lamp", NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()));
+        assert!(!inventory.existed_on("Bulb", on(19)));
+        assert!(inventory.existed_on("Bulb", on(20)));
+        assert!(!inventory.existed_on("Nothing", on(20)));
+    }
+}